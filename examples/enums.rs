@@ -0,0 +1,68 @@
+extern crate derive_divisible;
+// the mock traits below stand for the ones of rayon-adaptive
+extern crate self as rayon_adaptive;
+use derive_divisible::{Divisible, DivisibleIntoBlocks};
+
+struct IndexedPower();
+
+trait Divisible: Sized {
+    type Power;
+    fn base_length(&self) -> usize;
+    fn divide(self) -> (Self, Self);
+}
+
+impl<T> Divisible for &[T] {
+    type Power = IndexedPower;
+    fn base_length(&self) -> usize {
+        self.len()
+    }
+    fn divide(self) -> (Self, Self) {
+        let mid = self.len() / 2;
+        self.split_at(mid)
+    }
+}
+
+trait DivisibleIntoBlocks: Divisible {
+    fn divide_at(self, index: usize) -> (Self, Self);
+}
+
+impl<T> DivisibleIntoBlocks for &[T] {
+    fn divide_at(self, index: usize) -> (Self, Self) {
+        self.split_at(index)
+    }
+}
+
+#[derive(Divisible, DivisibleIntoBlocks, Debug)]
+#[power(IndexedPower)]
+// tuple, struct and unit variants all together
+enum Task<'a> {
+    Running {
+        #[divide_by(clone)]
+        state: String,
+        #[divide_by(default)]
+        processed: usize,
+        items: &'a [usize],
+    },
+    Waiting(#[divide_by(copy)] u64, &'a [usize]),
+    Idle,
+    #[divisible(infinite)]
+    Forever,
+}
+
+fn main() {
+    let items = vec![5, 6, 7];
+    let task = Task::Running {
+        state: "running".to_owned(),
+        processed: 2,
+        items: &items,
+    };
+    println!("l: {}", task.base_length());
+    let (left, right) = task.divide();
+    println!("left: {:?}, right: {:?}", left, right);
+
+    let (left, right) = Task::Waiting(7, &items).divide_at(1);
+    println!("left: {:?}, right: {:?}", left, right);
+
+    println!("l: {}", Task::Idle.base_length());
+    println!("l: {}", Task::Forever.base_length());
+}
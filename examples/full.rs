@@ -1,5 +1,7 @@
 extern crate derive_divisible;
-use derive_divisible::{Divisible, DivisibleAtIndex, DivisibleIntoBlocks};
+// the mock traits below stand for the ones of rayon-adaptive
+extern crate self as rayon_adaptive;
+use derive_divisible::{Divisible, DivisibleAtIndex, DivisibleIntoBlocks, FullyDivisible};
 
 struct IndexedPower();
 
@@ -41,20 +43,49 @@ struct Foo<'a, 'b, T: Sized + Copy> {
     foo: T,
     #[divide_by(default)]
     bar: f64,
+    #[divide_by(copy)]
+    id: u64,
+    #[divide_by(ignore)]
+    log: Vec<String>,
     baz: &'a [u32],
     baz2: &'b [f64],
 }
 
+#[derive(FullyDivisible, Debug)]
+#[power(IndexedPower)]
+struct Bar<'a> {
+    #[divide_by(clone)]
+    name: String,
+    data: &'a [u32],
+}
+
+fn divide_at_index<D: DivisibleAtIndex>(d: D, index: usize) -> (D, D) {
+    d.divide_at(index)
+}
+
 fn main() {
     let v1 = vec![1, 2, 3];
     let v2 = vec![2.4, 3.3];
     let f = Foo {
         foo: 3,
         bar: 0.5,
+        id: 42,
+        log: vec!["created".to_owned()],
         baz: &v1,
         baz2: &v2,
     };
     println!("l: {}", f.base_length());
     let (f1, f2) = f.divide();
     println!("left: {:?}, right: {:?}", f1, f2);
+    let (f3, f4) = divide_at_index(f2, 1);
+    println!("left: {:?}, right: {:?}", f3, f4);
+
+    let b = Bar {
+        name: "bar".to_owned(),
+        data: &v1,
+    };
+    println!("l: {}", b.base_length());
+    let (b1, b2) = divide_at_index(b, 2);
+    println!("left: {:?}, right: {:?}", b1, b2);
+    println!("divided: {:?}", b2.divide());
 }
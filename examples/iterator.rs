@@ -0,0 +1,113 @@
+extern crate derive_divisible;
+// the mock traits below stand for the ones of rayon-adaptive
+extern crate self as rayon_adaptive;
+use derive_divisible::{Divisible, Edible, ParallelIterator};
+use std::iter::Map;
+use std::ops::Range;
+
+struct IndexedPower();
+
+trait Divisible: Sized {
+    type Power;
+    fn base_length(&self) -> usize;
+    fn divide(self) -> (Self, Self);
+}
+
+trait ParallelIterator: Divisible {
+    type Item;
+    type SequentialIterator: Iterator<Item = Self::Item>;
+    fn iter(self, size: usize) -> (Self::SequentialIterator, Self);
+}
+
+trait Edible: Sized {
+    type Item;
+    type SequentialIterator: Iterator<Item = Self::Item>;
+    fn iter(self, size: usize) -> (Self::SequentialIterator, Self);
+}
+
+#[derive(Debug)]
+struct Indices(Range<usize>);
+
+impl Divisible for Indices {
+    type Power = IndexedPower;
+    fn base_length(&self) -> usize {
+        self.0.len()
+    }
+    fn divide(self) -> (Self, Self) {
+        let middle = self.0.start + self.0.len() / 2;
+        (Indices(self.0.start..middle), Indices(middle..self.0.end))
+    }
+}
+
+impl ParallelIterator for Indices {
+    type Item = usize;
+    type SequentialIterator = Range<usize>;
+    fn iter(self, size: usize) -> (Self::SequentialIterator, Self) {
+        let middle = self.0.start + size;
+        (self.0.start..middle, Indices(middle..self.0.end))
+    }
+}
+
+#[derive(Divisible, ParallelIterator)]
+#[power(IndexedPower)]
+#[item(u64)]
+#[sequential_iterator(Map<Range<usize>, fn(usize) -> u64>)]
+#[iterator_extraction(iter.map(self.op))]
+struct Mapped {
+    #[divisible(inner)]
+    indices: Indices,
+    #[divide_by(copy)]
+    op: fn(usize) -> u64,
+}
+
+#[derive(Debug)]
+struct Pile(Vec<u32>);
+
+impl Edible for Pile {
+    type Item = u32;
+    type SequentialIterator = std::vec::IntoIter<u32>;
+    fn iter(mut self, size: usize) -> (Self::SequentialIterator, Self) {
+        let rest = self.0.split_off(size);
+        (self.0.into_iter(), Pile(rest))
+    }
+}
+
+// generic inner iterators get an `Edible` bound
+#[derive(Edible)]
+struct Labeled<E> {
+    #[divisible(inner)]
+    pile: E,
+    label: &'static str,
+}
+
+fn square(x: usize) -> u64 {
+    (x * x) as u64
+}
+
+fn main() {
+    let mapped = Mapped {
+        indices: Indices(2..6),
+        op: square,
+    };
+    println!("l: {}", mapped.base_length());
+    let (left, right) = mapped.divide();
+    let (sequential, remaining) = right.iter(1);
+    println!(
+        "iterated: {:?}, remaining: {:?}",
+        sequential.collect::<Vec<_>>(),
+        remaining.indices
+    );
+    println!("sum: {}", left.iter(2).0.sum::<u64>());
+
+    let labeled = Labeled {
+        pile: Pile(vec![1, 2, 3]),
+        label: "pile",
+    };
+    let (sequential, remaining) = Edible::iter(labeled, 2);
+    println!(
+        "{}: iterated {:?}, remaining {:?}",
+        remaining.label,
+        sequential.collect::<Vec<_>>(),
+        remaining.pile
+    );
+}
@@ -0,0 +1,45 @@
+extern crate derive_divisible;
+// the mock trait below stands for a merging trait of rayon-adaptive
+extern crate self as rayon_adaptive;
+use derive_divisible::Merge;
+
+trait Merge {
+    fn merge(left: Self, right: Self) -> Self;
+}
+
+impl Merge for usize {
+    fn merge(left: Self, right: Self) -> Self {
+        left.max(right)
+    }
+}
+
+#[derive(Merge, Debug)]
+struct Statistics {
+    #[merge_by(extend)]
+    values: Vec<u32>,
+    #[merge_by(add)]
+    sum: u32,
+    longest: usize,
+    #[merge_by(left)]
+    first: Option<u32>,
+    #[merge_by(right)]
+    last: Option<u32>,
+}
+
+fn main() {
+    let left = Statistics {
+        values: vec![1, 2],
+        sum: 3,
+        longest: 2,
+        first: Some(1),
+        last: Some(2),
+    };
+    let right = Statistics {
+        values: vec![3],
+        sum: 3,
+        longest: 1,
+        first: Some(3),
+        last: Some(3),
+    };
+    println!("merged: {:?}", Merge::merge(left, right));
+}
@@ -0,0 +1,90 @@
+extern crate derive_divisible;
+// the mock traits below stand for the ones of rayon-adaptive
+extern crate self as rayon_adaptive;
+use derive_divisible::{Divisible, DivisibleIntoBlocks};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+struct IndexedPower();
+
+trait Divisible: Sized {
+    type Power;
+    fn base_length(&self) -> usize;
+    fn divide(self) -> (Self, Self);
+}
+
+impl<T> Divisible for &[T] {
+    type Power = IndexedPower;
+    fn base_length(&self) -> usize {
+        self.len()
+    }
+    fn divide(self) -> (Self, Self) {
+        let mid = self.len() / 2;
+        self.split_at(mid)
+    }
+}
+
+trait DivisibleIntoBlocks: Divisible {
+    fn divide_at(self, index: usize) -> (Self, Self);
+}
+
+impl<T> DivisibleIntoBlocks for &[T] {
+    fn divide_at(self, index: usize) -> (Self, Self) {
+        self.split_at(index)
+    }
+}
+
+mod budget {
+    pub fn split(budget: u32) -> (u32, u32) {
+        (budget / 2, budget - budget / 2)
+    }
+    pub fn split_at(budget: u32, index: usize) -> (u32, u32) {
+        let left = budget.min(index as u32);
+        (left, budget - left)
+    }
+}
+
+#[derive(Divisible, DivisibleIntoBlocks, Debug)]
+#[power(IndexedPower)]
+struct Job<'a> {
+    #[divide_by(fn = "budget::split", divide_at_fn = "budget::split_at")]
+    budget: u32,
+    #[divide_by(arc_clone)]
+    name: Arc<String>,
+    #[divide_by(default_left)]
+    output: Vec<u32>,
+    #[divide_by(reset)]
+    cache: HashMap<usize, u32>,
+    #[divide_by(ratio = 3/4)]
+    heavy: &'a [u32],
+    light: &'a [u32],
+}
+
+#[derive(Divisible, DivisibleIntoBlocks, Debug)]
+#[power(IndexedPower)]
+struct Text {
+    #[divide_by(string_chars)]
+    text: String,
+}
+
+fn main() {
+    let v = vec![1, 2, 3, 4];
+    let job = Job {
+        budget: 5,
+        name: Arc::new("job".to_owned()),
+        output: vec![0],
+        cache: (0..4).map(|i| (i, v[i])).collect(),
+        heavy: &v,
+        light: &v,
+    };
+    println!("l: {}", job.base_length());
+    let (left, right) = job.divide();
+    println!("left: {:?}, right: {:?}", left, right);
+    println!("divided: {:?}", right.divide_at(1));
+
+    let text = Text {
+        text: "héllo".to_owned(),
+    };
+    println!("l: {}", text.base_length());
+    println!("divided: {:?}", text.divide());
+}
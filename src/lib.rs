@@ -1,339 +1,2905 @@
 //! This crate provides automatic derivation for `rayon-adaptive`
 //! divisibility traits. If you don't know them you should go there first.
-//! By default it will just divide all fields but you can use attributes to specify
-//! two different behaviors.
-//! `clone` will instead clone the field to get the same value on both sides and
-//! `default` will keep the value on the left side and reset the value on a default value
-//! on the right side.
+//!
+//! # Derives
+//!
+//! - `Divisible`, `DivisibleIntoBlocks` and `DivisibleAtIndex` work on structs and enums.
+//!   `FullyDivisible` is a shortcut deriving all three at once.
+//! - `Merge` recombines the halves of a struct.
+//! - `ParallelIterator` and `Edible` extract sequential iterators from a struct.
+//!
+//! Generated code refers to the traits as `::rayon_adaptive::Divisible` (and so on)
+//! so they do not need to be in scope.
+//!
+//! # Field strategies
+//!
+//! By default all fields get divided. `#[divide_by(...)]` on a field picks another strategy:
+//!
+//! - `divisible`: divide the field (the default).
+//! - `clone`: clone the field on both sides.
+//! - `copy`: copy the field on both sides.
+//! - `arc_clone`, `rc_clone`, `mutex_clone`: share an `Arc`, `Rc` or `Arc<Mutex<T>>` on both sides.
+//! - `share`: clone any other cheap handle on both sides.
+//! - `default` (or `default = "expression"`): keep the value on the left, reset the right.
+//! - `default_left` (or `default_left = "expression"`): move the value to the right, reset the left.
+//! - `take` (or `take = "expression"`): keep an `Option` on the left, `None` on the right.
+//! - `ignore` (also spelled `reset`): reset the field on both sides.
+//! - `empty_vec`: keep a `Vec` on the left, start an empty one on the right.
+//! - `split_off`: cut a `Vec` or `VecDeque` in place, counting in the base length.
+//! - `deque_split`: cut a `VecDeque` by draining its back part into a new one.
+//! - `btree_split`: cut a `BTreeMap` at the key of its median entry.
+//! - `range_split`: cut a `Range<usize>` into two ranges, counting in the base length.
+//! - `string_chars`: cut a `String` at a char boundary, counting chars.
+//! - `ratio = 3/4`: cut a `DivisibleIntoBlocks` field at three quarters of its length.
+//! - `half`: cut a `DivisibleIntoBlocks` field at `base_length / 2`.
+//! - `no_length`: with `divisible`, `half` or `ratio`, divide without counting in the base length.
+//! - `fn = "split"` (or `with`): divide with a `fn(T) -> (T, T)`.
+//! - `divide_at_fn = "split_at"`: divide at an index with a `fn(T, usize) -> (T, T)`.
+//! - `with_index = "split_at"`: the same function for both, `divide` passing half the base length.
+//! - `method = "split"`, `method_at = "split_at"`: the same with methods of the field.
+//! - `dup_with = "duplicate"`: duplicate a field which is not `Clone` with a `fn(&T) -> T`.
+//!
+//! Strategy names can be quoted, like `#[divide_by("clone")]`,
+//! and arguments can be spread over several attributes.
+//! `PhantomData` fields are built again with `Default` so they never need an attribute.
+//!
+//! # Container attributes
+//!
+//! - `#[power(IndexedPower)]`: the `Power` of `Divisible`, defaulting to the one of a single divided field.
+//! - `#[divide_by(all = "clone")]`: the strategy of fields without their own `divide_by`.
+//! - `#[length_field = "field"]`: the base length is the one of the given field
+//!   instead of the minimum of the divided fields.
+//! - `#[divisible(length = self.end - self.start)]`: the whole `base_length` body.
+//! - `#[min_block_size = 64]`: adds an inherent `MIN_BLOCK_SIZE` constant.
+//! - `#[divisible(generate_try_divide)]`: adds a `try_divide` method returning `None` on empty data.
+//! - `#[divisible(generate_divide_n)]`: adds a `divide_n(n)` method returning `n` parts.
+//! - `#[divisible(transparent)]`: a single field struct delegates everything to its field.
+//! - `#[divisible(infinite)]`: a base length of `usize::MAX`, required when no field is divided.
+//! - `#[divisible(empty)]` on a unit variant: goes on the right side of exhausted data.
+//! - `#[divisible(deny_clone)]`: rejects any field divided with `clone`.
+//! - `#[divisible(no_bounds)]`: keeps the where clause as written.
+//! - `#[divisible(bound = "T: Divisible + Send")]`: replaces the generated bounds.
+//! - `#[divisible(crate = "path")]`: where the traits are re-exported.
+//! - `#[divisible(with_length)]`: `DivisibleIntoBlocks` also derives `Divisible`.
+//! - `#[divisible(with_blocks)]`: `DivisibleAtIndex` also derives `DivisibleIntoBlocks`.
+//!
+//! `#[power]`, `#[length_field]`, `#[min_block_size]` and `#[divisible(length = ...)]` only take
+//! effect where `Divisible` is derived: a sibling `#[derive(Divisible)]` or `with_length`.
+//! `DivisibleIntoBlocks` alone only checks them.
+//!
+//! Without `no_bounds` or `bound`, the impls get the bounds the strategies of generic fields require.
+//! Enum variants get divided with their own field attributes and unit variants have a base length of 0.
+//! Unions are rejected.
+//!
+//! # Merging and iterators
+//!
+//! `Merge` merges fields with `Merge` unless marked with `#[merge_by(extend)]`,
+//! `#[merge_by(add)]`, `#[merge_by(left)]` or `#[merge_by(right)]`.
+//!
+//! `ParallelIterator` and `Edible` extract the sequential iterator of the field
+//! marked with `#[divisible(inner)]`, other fields being kept as they are.
+//! `#[iterator_extraction(iter.map(self.op))]` turns the extracted `iter` into the final iterator,
+//! whose type is given by `#[sequential_iterator(...)]` and items by `#[item(...)]`
+//! (both defaulting to the ones of the inner iterator).
 extern crate proc_macro;
 
-use proc_macro2::{Group, TokenStream};
-use quote::quote;
-use syn::{parse_macro_input, Attribute, Data, DeriveInput, Fields};
+use proc_macro2::{Delimiter, Group, Literal, Span, TokenStream};
+use quote::{quote, quote_spanned};
+use syn::parse::{Parse, ParseStream, Parser};
+use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
+use syn::visit::{self, Visit};
+use syn::{
+    parse_macro_input, parse_quote, Attribute, Data, DeriveInput, Expr, ExprLit, Fields, Generics,
+    Ident, Index, Lit, LitInt, LitStr, MacroDelimiter, Member, Meta, MetaNameValue, Path, Token,
+    Type, WherePredicate,
+};
 
-#[proc_macro_derive(Divisible, attributes(divide_by, power))]
+#[proc_macro_derive(
+    Divisible,
+    attributes(divide_by, divisible, length_field, min_block_size, power)
+)]
 pub fn derive_divisible(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
-    let power = power_type(&input.attrs);
-    let name = input.ident;
-    let generics = input.generics;
+    derive(&input, expand_divisible).into()
+}
+
+/// Crate defining the divisibility traits used in generated code.
+const CRATE_ROOT: &str = "rayon_adaptive";
+
+/// Path to the crate defining the divisibility traits:
+/// `::rayon_adaptive` unless given with `#[divisible(crate = "path")]`.
+fn crate_root(options: &DivisibleOptions) -> syn::Result<Path> {
+    match options.string("crate") {
+        Some(path) => path.parse().map_err(|_| {
+            syn::Error::new(
+                path.span(),
+                format!(
+                    "invalid #[divisible(crate = ...)] path `{}`, \
+                     expected a path like \"my_facade::rayon\"",
+                    path.value()
+                ),
+            )
+        }),
+        None => Ok(default_crate_root()),
+    }
+}
+
+/// `::rayon_adaptive`, the default crate root.
+fn default_crate_root() -> Path {
+    let root = Ident::new(CRATE_ROOT, Span::call_site());
+    parse_quote!(::#root)
+}
+
+/// Validate the attributes shared by all derives into a plan, then run given expansion
+/// on it, turning errors into `compile_error!` invocations.
+/// All invalid attributes are reported together, code being generated only
+/// when everything is valid.
+fn derive(input: &DeriveInput, expand: fn(&Plan) -> syn::Result<TokenStream>) -> TokenStream {
+    Plan::new(input)
+        .and_then(|plan| expand(&plan))
+        .unwrap_or_else(|errors| errors.to_compile_error())
+}
+
+/// Errors gathered while validating, to be reported together.
+#[derive(Default)]
+struct Errors(Option<syn::Error>);
+
+impl Errors {
+    /// Keep given error along with the previous ones.
+    fn push(&mut self, error: syn::Error) {
+        match self.0 {
+            Some(ref mut errors) => errors.combine(error),
+            None => self.0 = Some(error),
+        }
+    }
+
+    /// The value of given result, if valid, keeping its error otherwise.
+    fn check<T>(&mut self, result: syn::Result<T>) -> Option<T> {
+        result.map_err(|error| self.push(error)).ok()
+    }
+
+    /// Fail with all gathered errors, if any.
+    fn finish(self) -> syn::Result<()> {
+        self.0.map_or(Ok(()), Err)
+    }
+}
+
+/// Everything the derives need to know about the type, validated once by `derive`
+/// so that expansions and generators never check attributes again.
+struct Plan<'a> {
+    input: &'a DeriveInput,
+    /// path to the crate defining the divisibility traits
+    root: Path,
+    /// the `#[divisible(...)]` options of the type
+    options: DivisibleOptions,
+    /// the generics bounded by the user, if given
+    /// with `#[divisible(no_bounds)]` or `#[divisible(bound = "...")]`
+    user_generics: Option<Generics>,
+    /// the struct or all enum variants
+    shapes: Vec<Shape<'a>>,
+    /// the wrapped field, for `#[divisible(transparent)]` structs
+    transparent: Option<Transparent>,
+}
+
+/// A struct or an enum variant, with the plans of its fields.
+struct Shape<'a> {
+    /// `Name` or `Name::Variant`
+    path: TokenStream,
+    fields: &'a Fields,
+    /// the `#[divisible(...)]` options of the variant (none for structs)
+    options: DivisibleOptions,
+    /// the plan of each field, in declaration order
+    plans: Vec<FieldPlan<'a>>,
+}
+
+/// How a field is divided, validated once.
+struct FieldPlan<'a> {
+    field: &'a syn::Field,
+    strategy: DivideBy,
+    /// the `#[divisible(...)]` options of the field
+    options: DivisibleOptions,
+}
+
+impl<'a> Plan<'a> {
+    /// Validate the attributes of the type, its variants and all fields,
+    /// combining all errors.
+    fn new(input: &'a DeriveInput) -> syn::Result<Self> {
+        let mut errors = Errors::default();
+        let options = errors
+            .check(DivisibleOptions::parse(&input.attrs, OptionsPlace::Type))
+            .unwrap_or_default();
+        let root = errors
+            .check(crate_root(&options))
+            .unwrap_or_else(default_crate_root);
+        let user_generics = errors
+            .check(user_bounded_generics(input, &options))
+            .flatten();
+        // fields are still checked when the fallback is invalid
+        let fallback = errors
+            .check(fallback_strategy(input))
+            .unwrap_or(DivideBy::Divisible { length: true });
+        let deny_clone = options.flag("deny_clone").is_some();
+        let shapes: Vec<Shape> = shapes(&input.ident, &input.data)
+            .into_iter()
+            .map(|(path, fields, attributes)| Shape {
+                path,
+                fields,
+                options: errors
+                    .check(DivisibleOptions::parse(attributes, OptionsPlace::Variant))
+                    .unwrap_or_default(),
+                plans: fields
+                    .iter()
+                    .filter_map(|field| errors.check(field_plan(field, &fallback, deny_clone)))
+                    .collect(),
+            })
+            .collect();
+        let transparent = errors
+            .check(transparent_field(input, &options, &shapes))
+            .flatten();
+        errors.finish()?;
+        Ok(Plan {
+            input,
+            root,
+            options,
+            user_generics,
+            shapes,
+            transparent,
+        })
+    }
+
+    /// Is the type marked with given `#[divisible(option)]`.
+    fn has_option(&self, option: &str) -> bool {
+        self.options.flag(option).is_some()
+    }
+
+    /// The plans of all fields, of all variants.
+    fn fields(&self) -> impl Iterator<Item = &FieldPlan<'a>> {
+        self.shapes.iter().flat_map(|shape| shape.plans.iter())
+    }
+}
+
+/// Validate the options and the strategy of a field,
+/// `fallback` being its strategy if it has no `divide_by` attribute.
+fn field_plan<'a>(
+    field: &'a syn::Field,
+    fallback: &DivideBy,
+    deny_clone: bool,
+) -> syn::Result<FieldPlan<'a>> {
+    let options = DivisibleOptions::parse(&field.attrs, OptionsPlace::Field);
+    let strategy = find_strategy(field, fallback).and_then(|strategy| match strategy {
+        DivideBy::Clone if deny_clone && !is_phantom(&field.ty) => Err(denied_clone(field)),
+        strategy => Ok(strategy),
+    });
+    match (options, strategy) {
+        (Ok(options), Ok(strategy)) => Ok(FieldPlan {
+            field,
+            strategy,
+            options,
+        }),
+        (Err(mut error), Err(other)) => {
+            error.combine(other);
+            Err(error)
+        }
+        (Err(error), _) | (_, Err(error)) => Err(error),
+    }
+}
+
+/// Error for a cloned field of a type marked with `#[divisible(deny_clone)]`,
+/// pointing at its `divide_by` attribute (or at the field if cloned by default).
+fn denied_clone(field: &syn::Field) -> syn::Error {
+    let field_name = field.ident.as_ref().map_or_else(
+        || "this field".to_owned(),
+        |name| format!("field `{}`", name),
+    );
+    let message = format!(
+        "{} would be cloned on every division but #[divisible(deny_clone)] is set, \
+         use `copy`, `default` or a cheap sharing strategy like `share` or `arc_clone` instead",
+        field_name
+    );
+    match attributes_named(&field.attrs, "divide_by").next() {
+        Some(attribute) => syn::Error::new_spanned(attribute, message),
+        None => syn::Error::new_spanned(field, message),
+    }
+}
+
+/// Generate the `Divisible` implementation.
+fn expand_divisible(plan: &Plan) -> syn::Result<TokenStream> {
+    let input = plan.input;
+    reject_unions(input, "Divisible")?;
+    reject_undividable_fields(plan)?;
+    let power = power_type(plan)?;
+    let name = &input.ident;
+    let root = &plan.root;
+    let generics = bounded_generics(plan, &parse_quote!(#root::Divisible));
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let len_expression = length_expression(plan)?;
+    let inline = inline_attribute(plan.transparent.is_some());
+    let min_block_size = min_block_size(input)?;
+    let methods = inherent_methods(plan, &generics);
+    // split all fields and rebuild both sides
+    let divide_expression = match plan.transparent {
+        Some(ref transparent) => {
+            generate_transparent_division(root, name, transparent, Division::Divide)
+        }
+        None => generate_divide_expression(plan, Division::Divide)?,
+    };
+
+    Ok(quote! {
+        impl #impl_generics #root::Divisible for #name #ty_generics #where_clause {
+            type Power = #power;
+            #inline
+            fn base_length(&self) -> usize {
+                #len_expression
+            }
+            #inline
+            fn divide(self) -> (Self, Self) {
+                #divide_expression
+            }
+        }
+        #min_block_size
+        #methods
+    })
+}
+
+/// Inherent methods built on the `Divisible` ones, generated when asked for with
+/// `#[divisible(generate_try_divide)]` and `#[divisible(generate_divide_n)]`.
+/// They get the same bounds as the trait impl since they call its methods.
+fn inherent_methods(plan: &Plan, generics: &Generics) -> Option<TokenStream> {
+    let name = &plan.input.ident;
+    let visibility = &plan.input.vis;
+    let root = &plan.root;
+    let mut methods = Vec::new();
+    if plan.has_option("generate_try_divide") {
+        methods.push(quote! {
+            /// Divide in two parts, unless there is nothing to divide.
+            #[inline]
+            #[must_use]
+            #visibility fn try_divide(self) -> ::core::option::Option<(Self, Self)> {
+                if #root::Divisible::base_length(&self) == 0 {
+                    return ::core::option::Option::None;
+                }
+                ::core::option::Option::Some(#root::Divisible::divide(self))
+            }
+        });
+    }
+    if plan.has_option("generate_divide_n") {
+        methods.push(quote! {
+            /// Divide in `n` parts by dividing the last part until there are enough.
+            #[inline]
+            #visibility fn divide_n(self, n: usize) -> ::std::vec::Vec<Self> {
+                let mut parts = ::std::vec![self];
+                while parts.len() < n {
+                    let last = parts.pop().unwrap();
+                    let (left, right) = #root::Divisible::divide(last);
+                    parts.push(left);
+                    parts.push(right);
+                }
+                parts.truncate(n);
+                parts
+            }
+        });
+    }
+    if methods.is_empty() {
+        return None;
+    }
     let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
-    // implement base_length
-    let len_expression = generate_len_expression(&input.data);
+    Some(quote! {
+        impl #impl_generics #name #ty_generics #where_clause {
+            #(#methods)*
+        }
+    })
+}
+
+/// Inherent `MIN_BLOCK_SIZE` constant given by `#[min_block_size = N]`
+/// (with the visibility of the type), telling schedulers not to divide below `N`.
+fn min_block_size(input: &DeriveInput) -> syn::Result<Option<TokenStream>> {
+    let attribute = match attributes_named(&input.attrs, "min_block_size").next() {
+        Some(attribute) => attribute,
+        None => return Ok(None),
+    };
+    let size = match attribute.meta {
+        Meta::NameValue(MetaNameValue {
+            value:
+                Expr::Lit(ExprLit {
+                    lit: Lit::Int(ref size),
+                    ..
+                }),
+            ..
+        }) => size.clone(),
+        _ => {
+            return Err(syn::Error::new_spanned(
+                attribute,
+                "expected #[min_block_size = N] with N a positive integer",
+            ))
+        }
+    };
+    let value = size.base10_parse::<usize>()?;
+    if value == 0 {
+        return Err(syn::Error::new(
+            size.span(),
+            "the minimum block size must be a positive integer",
+        ));
+    }
+    // without its suffix, if any
+    let span = size.span();
+    let mut size = Literal::usize_unsuffixed(value);
+    size.set_span(span);
+    let name = &input.ident;
+    let visibility = &input.vis;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(Some(quote! {
+        impl #impl_generics #name #ty_generics #where_clause {
+            #visibility const MIN_BLOCK_SIZE: usize = #size;
+        }
+    }))
+}
+
+/// Body of `base_length`, shared by the `Divisible` derive and
+/// the `DivisibleIntoBlocks` one with `#[divisible(with_length)]`.
+fn length_expression(plan: &Plan) -> syn::Result<TokenStream> {
+    let input = plan.input;
+    let root = &plan.root;
+    // an expression given with `#[divisible(length = ...)]` is used as is
+    if let Some(length) = plan.options.expression("length") {
+        if let Some(attribute) = attributes_named(&input.attrs, "length_field").next() {
+            return Err(syn::Error::new_spanned(
+                attribute,
+                "#[length_field] conflicts with #[divisible(length = ...)]",
+            ));
+        }
+        return Ok(quote!(#length));
+    }
+    let length_member = match length_field(plan)? {
+        Some(member) => Some(member),
+        None => plan
+            .transparent
+            .as_ref()
+            .map(|transparent| transparent.inner.clone()),
+    };
+    match length_member {
+        Some(member) => Ok(quote! {
+            #root::Divisible::base_length(&self.#member)
+        }),
+        None => generate_len_expression(plan),
+    }
+}
+
+/// Extract the field given by `#[length_field = "name"]` if any.
+/// It must be a divided field of a struct.
+fn length_field(plan: &Plan) -> syn::Result<Option<Member>> {
+    let input = plan.input;
+    let attribute = match attributes_named(&input.attrs, "length_field").next() {
+        Some(attribute) => attribute,
+        None => return Ok(None),
+    };
+    let name = match attribute.meta {
+        Meta::NameValue(MetaNameValue {
+            value:
+                Expr::Lit(ExprLit {
+                    lit: Lit::Str(ref name),
+                    ..
+                }),
+            ..
+        }) => name.clone(),
+        _ => {
+            return Err(syn::Error::new_spanned(
+                attribute,
+                "expected #[length_field = \"field_name\"]",
+            ))
+        }
+    };
+    if plan.transparent.is_some() {
+        return Err(syn::Error::new_spanned(
+            attribute,
+            "#[length_field] conflicts with #[divisible(transparent)], \
+             which already takes the length of the wrapped field",
+        ));
+    }
+    let shape = match (&input.data, plan.shapes.first()) {
+        (Data::Struct(_), Some(shape)) => shape,
+        _ => {
+            return Err(syn::Error::new_spanned(
+                attribute,
+                "#[length_field] only applies to structs",
+            ))
+        }
+    };
+    let member: Member = name.parse()?;
+    let field = members(shape.fields)
+        .into_iter()
+        .zip(&shape.plans)
+        .find(|(m, _)| match (m, &member) {
+            (Member::Named(a), Member::Named(b)) => a == b,
+            (Member::Unnamed(a), Member::Unnamed(b)) => a.index == b.index,
+            _ => false,
+        })
+        .map(|(_, field)| field)
+        .ok_or_else(|| {
+            syn::Error::new(
+                name.span(),
+                format!("no field named `{}` to compute the length", name.value()),
+            )
+        })?;
+    if !field.strategy.is_divisible() {
+        return Err(syn::Error::new(
+            name.span(),
+            format!(
+                "length field `{}` must be divided with the divisible strategy",
+                name.value()
+            ),
+        ));
+    }
+    Ok(Some(member))
+}
+
+/// The field wrapped by a `#[divisible(transparent)]` struct.
+struct Transparent {
+    /// the wrapped field, to which everything is delegated
+    inner: Member,
+    /// `PhantomData` fields, rebuilt on both sides
+    phantoms: Vec<Member>,
+}
+
+/// Find the wrapped field of `#[divisible(transparent)]` structs.
+/// All other fields must be `PhantomData` and the wrapped one must be divided
+/// (without `no_length`) since everything is delegated to it.
+fn transparent_field(
+    input: &DeriveInput,
+    options: &DivisibleOptions,
+    shapes: &[Shape],
+) -> syn::Result<Option<Transparent>> {
+    let marker = match options.flag("transparent") {
+        Some(marker) => marker,
+        None => return Ok(None),
+    };
+    let fields = match input.data {
+        Data::Struct(ref data) => &data.fields,
+        _ => {
+            return Err(syn::Error::new(
+                marker,
+                "#[divisible(transparent)] only applies to structs",
+            ))
+        }
+    };
+    let (phantoms, inner): (Vec<_>, Vec<_>) = members(fields)
+        .into_iter()
+        .zip(fields.iter())
+        .partition(|(_, field)| is_phantom(&field.ty));
+    let mut inner = inner.into_iter();
+    match (inner.next(), inner.next()) {
+        (Some((inner, field)), None) => {
+            // everything is delegated to the field: it cannot be split another way
+            // (invalid strategies being already reported)
+            let strategy = shapes
+                .iter()
+                .flat_map(|shape| &shape.plans)
+                .find(|plan| std::ptr::eq(plan.field, field))
+                .map(|plan| &plan.strategy);
+            match strategy {
+                None | Some(DivideBy::Divisible { length: true }) => (),
+                Some(_) => {
+                    let message = "#[divisible(transparent)] delegates division to the wrapped \
+                                   field, which must use the divisible strategy without no_length";
+                    return Err(match attributes_named(&field.attrs, "divide_by").next() {
+                        Some(attribute) => syn::Error::new_spanned(attribute, message),
+                        None => syn::Error::new_spanned(field, message),
+                    });
+                }
+            }
+            Ok(Some(Transparent {
+                inner,
+                phantoms: phantoms.into_iter().map(|(member, _)| member).collect(),
+            }))
+        }
+        (None, _) => Err(syn::Error::new(
+            marker,
+            "#[divisible(transparent)] requires a field to wrap",
+        )),
+        (Some(_), Some((_, field))) => Err(syn::Error::new_spanned(
+            field,
+            "#[divisible(transparent)] requires exactly one field which is not a PhantomData",
+        )),
+    }
+}
+
+/// Is given type a `PhantomData` (whatever the path leading to it).
+fn is_phantom(ty: &Type) -> bool {
+    match *ty {
+        Type::Path(ref path) => path
+            .path
+            .segments
+            .iter()
+            .last()
+            .map(|segment| segment.ident == "PhantomData")
+            .unwrap_or(false),
+        _ => false,
+    }
+}
+
+/// Inlining hint of the generated methods: wrappers of a transparent struct
+/// are always inlined since they only forward to their field.
+fn inline_attribute(transparent: bool) -> TokenStream {
+    if transparent {
+        quote!(#[inline(always)])
+    } else {
+        quote!(#[inline])
+    }
+}
+
+/// Generate the body of `divide` or `divide_at` for transparent structs:
+/// divide the inner field and wrap back both sides.
+fn generate_transparent_division(
+    root: &Path,
+    name: &Ident,
+    transparent: &Transparent,
+    division: Division,
+) -> TokenStream {
+    let inner = &transparent.inner;
+    let phantoms = &transparent.phantoms;
+    let split = match division {
+        Division::Divide => quote! {
+            #root::Divisible::divide(self.#inner)
+        },
+        Division::DivideAt => quote! {
+            #root::DivisibleIntoBlocks::divide_at(self.#inner, __derive_divisible_index)
+        },
+    };
+    quote! {
+        let (left, right) = #split;
+        (
+            #name {
+                #inner: left,
+                #(#phantoms: ::core::marker::PhantomData,)*
+            },
+            #name {
+                #inner: right,
+                #(#phantoms: ::core::marker::PhantomData,)*
+            }
+        )
+    }
+}
+
+/// Unions have no known active field we could divide so we reject them
+/// with an error pointing at their name.
+fn reject_unions(input: &DeriveInput, trait_name: &str) -> syn::Result<()> {
+    if let Data::Union(_) = input.data {
+        Err(syn::Error::new_spanned(
+            &input.ident,
+            format!("{} cannot be derived for unions", trait_name),
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+/// Reject divided fields whose type cannot be divided for sure, with a tailored error:
+/// types mentioning the type being derived (or `Self`), like `children: Vec<Tree>` in `Tree`,
+/// whose division would require the very impl being derived,
+/// sending trait resolution into an endless loop,
+/// and primitive scalars like `usize` which have no length.
+fn reject_undividable_fields(plan: &Plan) -> syn::Result<()> {
+    let input = plan.input;
+    let names = [input.ident.clone(), Ident::new("Self", Span::call_site())];
+    let parameters = type_parameters(&input.generics);
+    let mut errors = Errors::default();
+    for &FieldPlan {
+        field,
+        ref strategy,
+        ..
+    } in plan.fields()
+    {
+        let divided = strategy.is_divisible();
+        let message = match primitive_scalar(&field.ty) {
+            _ if !divided => continue,
+            Some(primitive) if !parameters.contains(primitive) => format!(
+                "`{}` cannot be divided, it has no length: use #[divide_by(copy)] \
+                 to get the value on both sides, #[divide_by(clone)] or #[divide_by(default)]",
+                primitive
+            ),
+            _ if mentions_types(&field.ty, &names) => format!(
+                "recursive division is not supported: this field contains `{}` itself, \
+                 use #[divide_by(clone)], #[divide_by(default)] or a custom splitter \
+                 with #[divide_by(fn = \"path\")]",
+                input.ident
+            ),
+            _ => continue,
+        };
+        errors.push(syn::Error::new_spanned(&field.ty, message));
+    }
+    errors.finish()
+}
+
+/// The name of given type if it is a bare primitive scalar, like `usize` or `bool`.
+fn primitive_scalar(ty: &Type) -> Option<&Ident> {
+    const PRIMITIVES: &[&str] = &[
+        "bool", "char", "f32", "f64", "i8", "i16", "i32", "i64", "i128", "isize", "u8", "u16",
+        "u32", "u64", "u128", "usize",
+    ];
+    match *ty {
+        Type::Path(ref path) if path.qself.is_none() => path
+            .path
+            .get_ident()
+            .filter(|ident| PRIMITIVES.iter().any(|primitive| *ident == primitive)),
+        _ => None,
+    }
+}
+
+/// All attributes with given name.
+fn attributes_named<'a>(
+    attributes: &'a [Attribute],
+    searched_attribute_name: &'a str,
+) -> impl Iterator<Item = &'a Attribute> + 'a {
+    attributes
+        .iter()
+        .filter(move |a| a.path().is_ident(searched_attribute_name))
+}
+
+/// Return argument of first attribute with given name.
+fn attributes_search(attributes: &[Attribute], searched_attribute_name: &str) -> Option<Group> {
+    attributes_named(attributes, searched_attribute_name)
+        .next()
+        .and_then(attribute_group)
+}
+
+/// The group of arguments of an attribute, like `(clone)` in `#[divide_by(clone)]`,
+/// spanning its delimiters.
+fn attribute_group(attribute: &Attribute) -> Option<Group> {
+    match attribute.meta {
+        Meta::List(ref list) => {
+            let (delimiter, span) = match list.delimiter {
+                MacroDelimiter::Paren(ref paren) => (Delimiter::Parenthesis, paren.span),
+                MacroDelimiter::Brace(ref brace) => (Delimiter::Brace, brace.span),
+                MacroDelimiter::Bracket(ref bracket) => (Delimiter::Bracket, bracket.span),
+            };
+            let mut group = Group::new(delimiter, list.tokens.clone());
+            group.set_span(span.join());
+            Some(group)
+        }
+        _ => None,
+    }
+}
+
+/// Where a `#[divisible(...)]` attribute is placed, deciding the options it accepts.
+#[derive(Clone, Copy)]
+enum OptionsPlace {
+    Type,
+    Variant,
+    Field,
+}
+
+/// The value an option of `#[divisible(...)]` takes, with an example for messages.
+#[derive(Clone, Copy)]
+enum OptionValue {
+    Flag,
+    String(&'static str),
+    Expression(&'static str),
+}
+
+/// Options accepted by `#[divisible(...)]` on types.
+const TYPE_OPTIONS: &[(&str, OptionValue)] = &[
+    ("crate", OptionValue::String("path")),
+    ("length", OptionValue::Expression("expression")),
+    ("infinite", OptionValue::Flag),
+    ("transparent", OptionValue::Flag),
+    ("with_length", OptionValue::Flag),
+    ("with_blocks", OptionValue::Flag),
+    ("no_bounds", OptionValue::Flag),
+    ("bound", OptionValue::String("predicates")),
+    ("deny_clone", OptionValue::Flag),
+    ("generate_try_divide", OptionValue::Flag),
+    ("generate_divide_n", OptionValue::Flag),
+];
+
+/// Options accepted by `#[divisible(...)]` on enum variants.
+const VARIANT_OPTIONS: &[(&str, OptionValue)] = &[
+    ("infinite", OptionValue::Flag),
+    ("empty", OptionValue::Flag),
+];
+
+/// Options accepted by `#[divisible(...)]` on fields.
+const FIELD_OPTIONS: &[(&str, OptionValue)] = &[("inner", OptionValue::Flag)];
+
+impl OptionsPlace {
+    fn options(self) -> &'static [(&'static str, OptionValue)] {
+        match self {
+            OptionsPlace::Type => TYPE_OPTIONS,
+            OptionsPlace::Variant => VARIANT_OPTIONS,
+            OptionsPlace::Field => FIELD_OPTIONS,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            OptionsPlace::Type => "types",
+            OptionsPlace::Variant => "variants",
+            OptionsPlace::Field => "fields",
+        }
+    }
+}
+
+/// How an option is written, like `crate = "path"`.
+fn option_usage(name: &str, value: OptionValue) -> String {
+    match value {
+        OptionValue::Flag => name.to_owned(),
+        OptionValue::String(example) => format!("{} = \"{}\"", name, example),
+        OptionValue::Expression(example) => format!("{} = {}", name, example),
+    }
+}
+
+/// The options of all `#[divisible(...)]` attributes of a type, a variant or a field,
+/// repeated attributes being merged.
+#[derive(Default)]
+struct DivisibleOptions {
+    /// each given option, at most once and with a value of the expected kind
+    options: Vec<Meta>,
+}
+
+impl DivisibleOptions {
+    /// Parse the `divisible` attributes among given ones, rejecting unknown,
+    /// repeated or badly written options (all errors being combined).
+    fn parse(attributes: &[Attribute], place: OptionsPlace) -> syn::Result<Self> {
+        let mut options: Vec<Meta> = Vec::new();
+        let mut errors = Errors::default();
+        for attribute in attributes_named(attributes, "divisible") {
+            let parsed = errors
+                .check(attribute.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated));
+            for option in parsed.into_iter().flatten() {
+                match check_option(&option, place) {
+                    Err(error) => errors.push(error),
+                    Ok(name) if options.iter().any(|o| o.path().is_ident(name)) => {
+                        errors.push(syn::Error::new_spanned(
+                            option.path(),
+                            format!("duplicate divisible option `{}`", name),
+                        ))
+                    }
+                    Ok(_) => options.push(option),
+                }
+            }
+        }
+        errors.finish()?;
+        Ok(DivisibleOptions { options })
+    }
+
+    /// Span of given flag (like `infinite`) if set.
+    fn flag(&self, name: &str) -> Option<Span> {
+        self.options
+            .iter()
+            .find(|option| option.path().is_ident(name))
+            .map(|option| option.path().span())
+    }
+
+    /// The string given to an option (like `"T: Send"` in `bound = "T: Send"`).
+    fn string(&self, name: &str) -> Option<&LitStr> {
+        self.expression(name).and_then(|value| match *value {
+            Expr::Lit(ExprLit {
+                lit: Lit::Str(ref string),
+                ..
+            }) => Some(string),
+            _ => None,
+        })
+    }
+
+    /// The expression given to an option (like `self.end - self.start` in
+    /// `length = self.end - self.start`).
+    fn expression(&self, name: &str) -> Option<&Expr> {
+        self.options.iter().find_map(|option| match *option {
+            Meta::NameValue(ref option) if option.path.is_ident(name) => Some(&option.value),
+            _ => None,
+        })
+    }
+}
+
+/// Check that an option is accepted at given place with the right kind of value,
+/// returning its name.
+fn check_option(option: &Meta, place: OptionsPlace) -> syn::Result<&'static str> {
+    let accepted = place.options();
+    let given = option
+        .path()
+        .get_ident()
+        .map(Ident::to_string)
+        .unwrap_or_default();
+    let (name, value) = match accepted.iter().find(|(name, _)| *name == given) {
+        Some(&accepted) => accepted,
+        None => {
+            let suggestion = accepted
+                .iter()
+                .map(|&(name, _)| (edit_distance(&given, name), name))
+                .filter(|&(distance, name)| distance <= (name.len() / 3).max(1))
+                .min();
+            let message = match suggestion {
+                Some((_, name)) => format!(
+                    "unknown divisible option `{}`, did you mean `{}`?",
+                    given, name
+                ),
+                None => format!(
+                    "unknown divisible option `{}` for {}, expected one of: {}",
+                    given,
+                    place.name(),
+                    accepted
+                        .iter()
+                        .map(|&(name, value)| option_usage(name, value))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+            };
+            return Err(syn::Error::new_spanned(option.path(), message));
+        }
+    };
+    let valid = match (value, option) {
+        (OptionValue::Flag, Meta::Path(_)) => true,
+        (OptionValue::String(_), Meta::NameValue(option)) => matches!(
+            option.value,
+            Expr::Lit(ExprLit {
+                lit: Lit::Str(_),
+                ..
+            })
+        ),
+        (OptionValue::Expression(_), Meta::NameValue(_)) => true,
+        _ => false,
+    };
+    if valid {
+        Ok(name)
+    } else {
+        Err(syn::Error::new_spanned(
+            option,
+            format!("expected #[divisible({})]", option_usage(name, value)),
+        ))
+    }
+}
+
+/// Extract power attribute's value.
+/// If missing, single divided field structs get the power of their field,
+/// otherwise the error points at the type we derive for.
+/// If repeated or not holding a type, the error points at the attribute.
+fn power_type(plan: &Plan) -> syn::Result<Type> {
+    let input = plan.input;
+    let mut powers = attributes_named(&input.attrs, "power");
+    if let (Some(first), Some(duplicate)) = (powers.next(), powers.next()) {
+        return Err(syn::Error::new_spanned(
+            duplicate,
+            format!(
+                "duplicate #[power] attribute, the power is already given by #[power{}]",
+                attribute_group(first).map_or_else(String::new, |group| group.to_string())
+            ),
+        ));
+    }
+    if attributes_search(&input.attrs, "power").is_none() {
+        if let Some(field) = single_divided_field(plan) {
+            let root = &plan.root;
+            let ty = &field.ty;
+            return Ok(parse_quote!(<#ty as #root::Divisible>::Power));
+        }
+    }
+    let power = attributes_search(&input.attrs, "power").ok_or_else(|| {
+        syn::Error::new_spanned(
+            &input.ident,
+            "#[derive(Divisible)] requires a #[power(SomeType)] attribute \
+             giving the power of the type, for example #[power(IndexedPower)] \
+             (only structs with a single divided field get the power of their field)",
+        )
+    })?;
+    if power.stream().is_empty() {
+        return Err(syn::Error::new(
+            power.span(),
+            "a power type is required inside the parentheses, like #[power(IndexedPower)]",
+        ));
+    }
+    let power_type: Type = syn::parse2(power.stream()).map_err(|_| {
+        syn::Error::new(
+            power.span(),
+            format!(
+                "#[power] expects a single type, like #[power(IndexedPower)], found `{}`",
+                power.stream()
+            ),
+        )
+    })?;
+    check_power_parameter(&power_type, &input.generics)?;
+    Ok(power_type)
+}
+
+/// The only field of a struct (not counting `PhantomData` markers), if divided.
+fn single_divided_field<'a>(plan: &Plan<'a>) -> Option<&'a syn::Field> {
+    let fields: Vec<&FieldPlan> = match plan.input.data {
+        Data::Struct(_) => plan.fields().filter(|f| !is_phantom(&f.field.ty)).collect(),
+        _ => return None,
+    };
+    match fields[..] {
+        [field] if field.strategy.is_divisible() => Some(field.field),
+        _ => None,
+    }
+}
+
+/// A power given by a lone name looking like one of the type parameters
+/// but not declared is most likely a misspelled parameter:
+/// reject it here rather than letting rustc complain inside the generated code.
+fn check_power_parameter(power: &Type, generics: &Generics) -> syn::Result<()> {
+    let name = match *power {
+        Type::Path(ref path) if path.qself.is_none() && path.path.leading_colon.is_none() => {
+            match path.path.segments.first() {
+                Some(segment) if path.path.segments.len() == 1 && segment.arguments.is_empty() => {
+                    &segment.ident
+                }
+                _ => return Ok(()),
+            }
+        }
+        _ => return Ok(()),
+    };
+    let parameters = type_parameters(generics);
+    if parameters.contains(name) {
+        return Ok(());
+    }
+    let string = name.to_string();
+    let looks_like_parameter = parameters.iter().any(|parameter| {
+        let parameter = parameter.to_string();
+        parameter.starts_with(&string)
+            || edit_distance(&string, &parameter) <= (parameter.len() / 3).max(1)
+    });
+    if looks_like_parameter {
+        let parameters: Vec<String> = parameters
+            .iter()
+            .map(|parameter| format!("`{}`", parameter))
+            .collect();
+        return Err(syn::Error::new(
+            name.span(),
+            format!(
+                "power `{}` is not a type parameter, declared type parameters are: {}",
+                name,
+                parameters.join(", ")
+            ),
+        ));
+    }
+    Ok(())
+}
+
+// `power`, `length_field` and `min_block_size` are registered for
+// `#[divisible(with_length)]` which also derives `Divisible`.
+#[proc_macro_derive(
+    DivisibleIntoBlocks,
+    attributes(divide_by, divisible, length_field, min_block_size, power)
+)]
+pub fn derive_divisible_into_blocks(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    derive(&input, expand_divisible_into_blocks).into()
+}
+
+/// Generate the `DivisibleIntoBlocks` implementation
+/// (and the `Divisible` one if marked with `#[divisible(with_length)]`).
+fn expand_divisible_into_blocks(plan: &Plan) -> syn::Result<TokenStream> {
+    let divisible = if plan.has_option("with_length") {
+        Some(expand_divisible(plan)?)
+    } else {
+        check_divisible_attributes(plan)?;
+        None
+    };
+    let divisible_into_blocks = divisible_into_blocks_impl(plan)?;
+    Ok(quote! {
+        #divisible
+        #divisible_into_blocks
+    })
+}
+
+/// Check the attributes only read when deriving `Divisible`, so that invalid ones
+/// are reported even when `DivisibleIntoBlocks` is derived alone
+/// (they are then used by a sibling `Divisible` derive, if any).
+fn check_divisible_attributes(plan: &Plan) -> syn::Result<()> {
+    let mut errors = Errors::default();
+    if attributes_named(&plan.input.attrs, "power")
+        .next()
+        .is_some()
+    {
+        errors.check(power_type(plan));
+    }
+    errors.check(min_block_size(plan.input));
+    errors.check(length_field(plan));
+    errors.finish()
+}
+
+/// Generate the `DivisibleIntoBlocks` implementation alone.
+fn divisible_into_blocks_impl(plan: &Plan) -> syn::Result<TokenStream> {
+    let input = plan.input;
+    reject_unions(input, "DivisibleIntoBlocks")?;
+    reject_undividable_fields(plan)?;
+    let name = &input.ident;
+    let root = &plan.root;
+    let generics = bounded_generics(plan, &parse_quote!(#root::DivisibleIntoBlocks));
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    // split all fields at index and rebuild both sides.
+    // the index gets a prefixed name like the fields bindings.
+    let inline = inline_attribute(plan.transparent.is_some());
+    let divide_expression = match plan.transparent {
+        Some(ref transparent) => {
+            generate_transparent_division(root, name, transparent, Division::DivideAt)
+        }
+        None => generate_divide_expression(plan, Division::DivideAt)?,
+    };
+
+    Ok(quote! {
+        impl #impl_generics #root::DivisibleIntoBlocks for #name #ty_generics #where_clause {
+            #inline
+            fn divide_at(self, __derive_divisible_index: usize) -> (Self, Self) {
+                #divide_expression
+            }
+        }
+    })
+}
+
+#[proc_macro_derive(DivisibleAtIndex, attributes(divide_by, divisible))]
+pub fn derive_divisible_at_index(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    derive(&input, expand_divisible_at_index).into()
+}
+
+/// Generate the `DivisibleAtIndex` implementation
+/// (and the `DivisibleIntoBlocks` one if marked with `#[divisible(with_blocks)]`).
+/// A derive cannot see the other ones so this has to be asked for.
+fn expand_divisible_at_index(plan: &Plan) -> syn::Result<TokenStream> {
+    let divisible_into_blocks = if plan.has_option("with_blocks") {
+        Some(divisible_into_blocks_impl(plan)?)
+    } else {
+        None
+    };
+    let divisible_at_index = divisible_at_index_impl(plan)?;
+    Ok(quote! {
+        #divisible_into_blocks
+        #divisible_at_index
+    })
+}
+
+/// Generate the `DivisibleAtIndex` implementation alone.
+fn divisible_at_index_impl(plan: &Plan) -> syn::Result<TokenStream> {
+    reject_unions(plan.input, "DivisibleAtIndex")?;
+    let name = &plan.input.ident;
+    let root = &plan.root;
+    let generics = bounded_generics(plan, &parse_quote!(#root::DivisibleAtIndex));
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics #root::DivisibleAtIndex for #name #ty_generics #where_clause {}
+    })
+}
+
+#[proc_macro_derive(
+    FullyDivisible,
+    attributes(divide_by, divisible, length_field, min_block_size, power)
+)]
+pub fn derive_fully_divisible(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    derive(&input, expand_fully_divisible).into()
+}
+
+/// Generate the `Divisible`, `DivisibleIntoBlocks` and `DivisibleAtIndex` implementations.
+fn expand_fully_divisible(plan: &Plan) -> syn::Result<TokenStream> {
+    let divisible = expand_divisible(plan)?;
+    let divisible_into_blocks = divisible_into_blocks_impl(plan)?;
+    let divisible_at_index = divisible_at_index_impl(plan)?;
+    Ok(quote! {
+        #divisible
+        #divisible_into_blocks
+        #divisible_at_index
+    })
+}
+
+#[proc_macro_derive(Merge, attributes(merge_by, divisible))]
+pub fn derive_merge(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    derive(&input, expand_merge).into()
+}
+
+/// Generate the `Merge` implementation.
+fn expand_merge(plan: &Plan) -> syn::Result<TokenStream> {
+    let input = plan.input;
+    let fields = match input.data {
+        Data::Struct(ref data) => &data.fields,
+        _ => {
+            return Err(syn::Error::new_spanned(
+                &input.ident,
+                "Merge can only be derived for structs",
+            ))
+        }
+    };
+    let name = &input.ident;
+    let root = &plan.root;
+    let generics = merge_generics(plan, fields)?;
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let merge_expression = generate_merge_expression(root, name, fields)?;
+    Ok(quote! {
+        impl #impl_generics #root::Merge for #name #ty_generics #where_clause {
+            fn merge(left: Self, right: Self) -> Self {
+                #merge_expression
+            }
+        }
+    })
+}
+
+/// What strategy to apply when merging a field.
+enum MergeBy {
+    /// Merge both sides with `Merge`
+    Merge,
+    /// Extend the left side with the right one
+    Extend,
+    /// Add both sides
+    Add,
+    /// Keep the left side
+    Left,
+    /// Keep the right side
+    Right,
+}
+
+/// All strategies accepted by the `merge_by` attribute.
+const MERGE_STRATEGIES: &[&str] = &["merge", "extend", "add", "left", "right"];
+
+/// Find what merge strategy to use for given field,
+/// `merge` if no `merge_by` attribute is given.
+fn find_merge_strategy(field: &syn::Field) -> syn::Result<MergeBy> {
+    let mut attributes = attributes_named(&field.attrs, "merge_by");
+    let attribute = match attributes.next() {
+        Some(attribute) => attribute,
+        None => return Ok(MergeBy::Merge),
+    };
+    if let Some(duplicate) = attributes.next() {
+        return Err(syn::Error::new_spanned(
+            duplicate,
+            "a field can only have one #[merge_by] strategy",
+        ));
+    }
+    let name = attribute_group(attribute)
+        .and_then(|group| syn::parse2::<Ident>(group.stream()).ok())
+        .ok_or_else(|| {
+            syn::Error::new_spanned(
+                attribute,
+                format!(
+                    "#[merge_by] requires a strategy in parentheses, one of: {}",
+                    MERGE_STRATEGIES.join(", ")
+                ),
+            )
+        })?;
+    match name.to_string().as_str() {
+        "merge" => Ok(MergeBy::Merge),
+        "extend" => Ok(MergeBy::Extend),
+        "add" => Ok(MergeBy::Add),
+        "left" => Ok(MergeBy::Left),
+        "right" => Ok(MergeBy::Right),
+        unknown => Err(syn::Error::new(
+            name.span(),
+            format!(
+                "unknown merge_by strategy `{}`, expected one of: {}",
+                unknown,
+                MERGE_STRATEGIES.join(", ")
+            ),
+        )),
+    }
+}
+
+/// Generics of the derived `Merge` impl, bounded like the division ones
+/// (only generic field types get bounds).
+fn merge_generics(plan: &Plan, fields: &Fields) -> syn::Result<Generics> {
+    if let Some(ref generics) = plan.user_generics {
+        return Ok(generics.clone());
+    }
+    let input = plan.input;
+    let root = &plan.root;
+    let parameters = type_parameters(&input.generics);
+    let mut predicates = Vec::new();
+    for field in fields {
+        let ty = &field.ty;
+        let strategy = find_merge_strategy(field)?;
+        if !uses_parameters(ty, &parameters) {
+            continue;
+        }
+        match strategy {
+            MergeBy::Merge => predicates.push(field_bound(ty, quote!(#root::Merge))),
+            MergeBy::Extend => predicates.push(field_bound(
+                ty,
+                quote! {
+                    ::core::iter::IntoIterator
+                        + ::core::iter::Extend<<#ty as ::core::iter::IntoIterator>::Item>
+                },
+            )),
+            MergeBy::Add => {
+                predicates.push(field_bound(ty, quote!(::core::ops::Add<Output = #ty>)))
+            }
+            MergeBy::Left | MergeBy::Right => (),
+        }
+    }
+    Ok(add_predicates(&input.generics, predicates))
+}
+
+/// Generate the struct literal merging all fields of `left` and `right`.
+fn generate_merge_expression(
+    root: &Path,
+    name: &Ident,
+    fields: &Fields,
+) -> syn::Result<TokenStream> {
+    let members = members(fields);
+    let merged = fields
+        .iter()
+        .zip(&members)
+        .map(|(f, member)| {
+            // spanned on the field type so that missing impls are reported on the field
+            let ty = &f.ty;
+            let span = ty.span();
+            Ok(match find_merge_strategy(f)? {
+                MergeBy::Merge => quote_spanned! {span=>
+                    <#ty as #root::Merge>::merge(left.#member, right.#member)
+                },
+                MergeBy::Extend => quote_spanned! {span=>
+                    {
+                        let mut merged = left.#member;
+                        ::core::iter::Extend::extend(&mut merged, right.#member);
+                        merged
+                    }
+                },
+                MergeBy::Add => quote_spanned! {span=>
+                    left.#member + right.#member
+                },
+                MergeBy::Left => quote!(left.#member),
+                MergeBy::Right => quote!(right.#member),
+            })
+        })
+        .collect::<syn::Result<Vec<_>>>()?;
+    Ok(quote! {
+        #name { #(#members: #merged, )* }
+    })
+}
+
+#[proc_macro_derive(
+    ParallelIterator,
+    attributes(item, sequential_iterator, iterator_extraction, power, divisible)
+)]
+pub fn derive_parallel_iterator(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    derive(&input, expand_parallel_iterator).into()
+}
+
+#[proc_macro_derive(
+    Edible,
+    attributes(item, sequential_iterator, iterator_extraction, divisible)
+)]
+pub fn derive_edible(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    derive(&input, expand_edible).into()
+}
+
+/// Generate the `ParallelIterator` implementation.
+fn expand_parallel_iterator(plan: &Plan) -> syn::Result<TokenStream> {
+    expand_iterator(plan, "ParallelIterator", true)
+}
+
+/// Generate the `Edible` implementation.
+fn expand_edible(plan: &Plan) -> syn::Result<TokenStream> {
+    expand_iterator(plan, "Edible", false)
+}
+
+/// Generate the implementation of an iterator trait (`ParallelIterator` or `Edible`):
+/// sequential iterators are extracted from the inner iterator field (implementing the same trait),
+/// all other fields being kept in the remaining part.
+/// Traits extending `Divisible` (`divisible`) also need the bounds of its impl.
+fn expand_iterator(plan: &Plan, trait_name: &str, divisible: bool) -> syn::Result<TokenStream> {
+    let input = plan.input;
+    let shape = match (&input.data, plan.shapes.first()) {
+        (Data::Struct(_), Some(shape)) => shape,
+        _ => {
+            return Err(syn::Error::new_spanned(
+                &input.ident,
+                format!("{} can only be derived for structs", trait_name),
+            ))
+        }
+    };
+    let fields = shape.fields;
+    let name = &input.ident;
+    let root = &plan.root;
+    let iterator_trait = Ident::new(trait_name, Span::call_site());
+    let iterator_trait: Path = parse_quote!(#root::#iterator_trait);
+    let (inner, ty) = iterator_field(input, shape, trait_name)?;
+    let extraction: Option<Expr> = type_attribute(input, "iterator_extraction", "iter.map(f)")?;
+    let sequential: Type = match (
+        type_attribute(input, "sequential_iterator", "std::vec::IntoIter<u32>")?,
+        &extraction,
+    ) {
+        (Some(sequential), _) => sequential,
+        (None, None) => parse_quote!(<#ty as #iterator_trait>::SequentialIterator),
+        (None, Some(extraction)) => {
+            return Err(syn::Error::new_spanned(
+                extraction,
+                "#[iterator_extraction] changes the sequential iterator, \
+                 its type must be given with #[sequential_iterator(...)]",
+            ))
+        }
+    };
+    let item: Type = match type_attribute(input, "item", "u32")? {
+        Some(item) => item,
+        None => parse_quote!(<#sequential as ::core::iter::Iterator>::Item),
+    };
+    let extraction = extraction.map_or_else(|| quote!(iter), |extraction| quote!(#extraction));
+    let generics = match plan.user_generics {
+        Some(ref generics) => generics.clone(),
+        None => {
+            let generics = if divisible {
+                bounded_generics(plan, &parse_quote!(#root::Divisible))
+            } else {
+                input.generics.clone()
+            };
+            if uses_parameters(ty, &type_parameters(&input.generics)) {
+                add_predicates(&generics, vec![field_bound(ty, quote!(#iterator_trait))])
+            } else {
+                generics
+            }
+        }
+    };
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    // all other fields stay in the remaining part
+    let others = if fields.len() > 1 {
+        quote!(..self)
+    } else {
+        quote!()
+    };
+    let extract = quote_spanned! {ty.span()=>
+        <#ty as #iterator_trait>::iter(self.#inner, size)
+    };
+    Ok(quote! {
+        impl #impl_generics #iterator_trait for #name #ty_generics #where_clause {
+            type Item = #item;
+            type SequentialIterator = #sequential;
+            #[inline]
+            fn iter(self, size: usize) -> (Self::SequentialIterator, Self) {
+                let (iter, __derive_divisible_remaining) = #extract;
+                let __derive_divisible_sequential = #extraction;
+                (
+                    __derive_divisible_sequential,
+                    #name {
+                        #inner: __derive_divisible_remaining,
+                        #others
+                    },
+                )
+            }
+        }
+    })
+}
+
+/// The field sequential iterators are extracted from,
+/// the only one marked with `#[divisible(inner)]`.
+fn iterator_field<'a>(
+    input: &DeriveInput,
+    shape: &Shape<'a>,
+    trait_name: &str,
+) -> syn::Result<(Member, &'a Type)> {
+    let mut inner = None;
+    for (member, plan) in members(shape.fields).into_iter().zip(&shape.plans) {
+        let marker = match plan.options.flag("inner") {
+            Some(marker) => marker,
+            None => continue,
+        };
+        if inner.is_some() {
+            return Err(syn::Error::new(
+                marker,
+                "only one field can be marked with #[divisible(inner)]",
+            ));
+        }
+        inner = Some((member, &plan.field.ty));
+    }
+    inner.ok_or_else(|| {
+        syn::Error::new_spanned(
+            &input.ident,
+            format!(
+                "{} requires the field sequential iterators are extracted from \
+                 to be marked with #[divisible(inner)]",
+                trait_name
+            ),
+        )
+    })
+}
+
+/// Parse the argument of a type level attribute like `#[item(u32)]`, if given.
+fn type_attribute<T: Parse>(
+    input: &DeriveInput,
+    attribute_name: &str,
+    example: &str,
+) -> syn::Result<Option<T>> {
+    let attribute = match attributes_named(&input.attrs, attribute_name).next() {
+        Some(attribute) => attribute,
+        None => return Ok(None),
+    };
+    attribute_group(attribute)
+        .and_then(|group| syn::parse2(group.stream()).ok())
+        .map(Some)
+        .ok_or_else(|| {
+            syn::Error::new_spanned(
+                attribute,
+                format!(
+                    "#[{}] expects a single argument in parentheses, like #[{}({})]",
+                    attribute_name, attribute_name, example
+                ),
+            )
+        })
+}
+
+/// Which division method we are generating.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Division {
+    /// `divide`, cutting in two halves
+    Divide,
+    /// `divide_at`, cutting at given index
+    DivideAt,
+}
+
+/// What strategy to apply when dividing a field.
+#[derive(Clone)]
+enum DivideBy {
+    /// Clone the field
+    Clone,
+    /// Share the `Arc` field on both sides
+    ArcClone,
+    /// Share the `Rc` field on both sides
+    RcClone,
+    /// Share the `Arc<Mutex<T>>` field on both sides
+    MutexClone,
+    /// Clone a reference counted field, sharing its content on both sides
+    Share,
+    /// Copy the field on both sides
+    Copy,
+    /// Take a default value on right side and move on the left
+    Default,
+    /// Take a default value on left side and move on the right
+    DefaultLeft,
+    /// Evaluate given expression on the left and move the field on the right
+    /// (`default_left = "expression"`)
+    DefaultLeftValue(Expr),
+    /// Move the field on the left and evaluate given expression on the right
+    /// (`default = "expression"` or `take = "expression"`)
+    DefaultValue(Expr),
+    /// Move the `Option` on the left and leave `None` on the right
+    Take,
+    /// Move the `Vec` on the left and start an empty one on the right
+    EmptyVec,
+    /// Cut a `Vec` (or `VecDeque`) in place with its own `split_off`,
+    /// its length contributing to the base length
+    SplitOff,
+    /// Cut a `VecDeque` by draining its back part into a new one,
+    /// its length contributing to the base length
+    DequeSplit,
+    /// Cut a `BTreeMap` with its own `split_off` at the key of the median
+    /// (or of the index) entry, its length contributing to the base length
+    BtreeSplit,
+    /// Cut a `Range<usize>` in two ranges, its length contributing to the base length
+    RangeSplit,
+    /// Cut a `String` at a char boundary, its number of chars contributing to the base length
+    StringChars,
+    /// Take a default value on both sides, not contributing to the length
+    Ignore,
+    /// Divide using divisible, the field contributing to the base length
+    /// unless marked `no_length`
+    Divisible { length: bool },
+    /// Divide using divisible but cut `divide` at given fraction of the field length
+    /// (`ratio = numerator/denominator`) instead of its middle,
+    /// the field contributing to the base length unless marked `no_length`
+    Ratio {
+        numerator: u64,
+        denominator: u64,
+        length: bool,
+    },
+    /// Divide using user provided functions or methods
+    /// (`fn = "path"` or `method = "name"` for `divide`
+    /// and `divide_at_fn = "path"` or `method_at = "name"` for `divide_at`)
+    CustomFn {
+        divide: Option<Splitter>,
+        divide_at: Option<Splitter>,
+    },
+    /// Duplicate the field with a user provided `fn(&T) -> T` (`dup_with = "path"`),
+    /// the copy going on the left
+    DupFn(Path),
+    /// Divide using a user provided `fn(T, usize) -> (T, T)` (`with_index = "path"`),
+    /// called with the index by `divide_at` and with half the base length by `divide`
+    IndexFn(Path),
+}
+
+/// A user provided way to split a field.
+#[derive(Clone)]
+enum Splitter {
+    /// A function taking the field
+    Function(Path),
+    /// A method of the field
+    Method(Ident),
+}
+
+impl Splitter {
+    /// Call on given field binding, with given index (for `divide_at`),
+    /// spanned so that method resolution errors are reported on the field.
+    fn call(&self, binding: &Ident, index: Option<TokenStream>, span: Span) -> TokenStream {
+        match *self {
+            Splitter::Function(ref function) => quote! {
+                #function(#binding, #index)
+            },
+            Splitter::Method(ref method) => quote_spanned! {span=>
+                #binding.#method(#index)
+            },
+        }
+    }
+}
 
-    // split into tuple of couples (left and right)
-    let split_expression = generate_split_declarations(&input.data);
-    // move tuple into fields of split structure
-    let left_fields = generate_fields(&input.data, 0);
-    let right_fields = generate_fields(&input.data, 1);
+impl DivideBy {
+    /// Is the field divided as a `Divisible`.
+    fn is_divisible(&self) -> bool {
+        matches!(*self, DivideBy::Divisible { .. } | DivideBy::Ratio { .. })
+    }
 
-    let expanded = quote! {
-        impl #impl_generics Divisible for #name #ty_generics #where_clause {
-            type Power = #power;
-            fn base_length(&self) -> usize {
-                #len_expression
+    /// Does the field contribute to the base length.
+    fn has_length(&self) -> bool {
+        matches!(
+            *self,
+            DivideBy::Divisible { length: true }
+                | DivideBy::Ratio { length: true, .. }
+                | DivideBy::SplitOff
+                | DivideBy::DequeSplit
+                | DivideBy::BtreeSplit
+                | DivideBy::RangeSplit
+                | DivideBy::StringChars
+        )
+    }
+}
+
+/// Strategies of the `divide_by` attribute given by name alone, like `clone`.
+const NAMED_STRATEGIES: &[(&str, DivideBy)] = &[
+    ("clone", DivideBy::Clone),
+    ("arc_clone", DivideBy::ArcClone),
+    ("rc_clone", DivideBy::RcClone),
+    ("mutex_clone", DivideBy::MutexClone),
+    ("share", DivideBy::Share),
+    ("copy", DivideBy::Copy),
+    ("default", DivideBy::Default),
+    ("default_left", DivideBy::DefaultLeft),
+    ("take", DivideBy::Take),
+    ("empty_vec", DivideBy::EmptyVec),
+    ("split_off", DivideBy::SplitOff),
+    ("deque_split", DivideBy::DequeSplit),
+    ("btree_split", DivideBy::BtreeSplit),
+    ("range_split", DivideBy::RangeSplit),
+    ("string_chars", DivideBy::StringChars),
+    ("ignore", DivideBy::Ignore),
+    // for caches which neither side should inherit
+    ("reset", DivideBy::Ignore),
+    ("divisible", DivideBy::Divisible { length: true }),
+    // cut at the middle of the field's own length rather than with `divide`
+    (
+        "half",
+        DivideBy::Ratio {
+            numerator: 1,
+            denominator: 2,
+            length: true,
+        },
+    ),
+];
+
+/// Parser of the part following the name of a `divide_by` argument taking a value.
+type ValueParser = fn(ParseStream) -> syn::Result<StrategyArgument>;
+
+/// Arguments of the `divide_by` attribute taking a value, like `fn = "path"`,
+/// with the way they are written in messages and the parser of their value.
+const VALUED_STRATEGIES: &[(&str, &str, ValueParser)] = &[
+    ("ratio", "ratio = N/D", parse_ratio),
+    ("default", "default = \"expression\"", |input| {
+        parse_default(input, "default")
+    }),
+    ("default_left", "default_left = \"expression\"", |input| {
+        parse_default(input, "default_left")
+    }),
+    ("take", "take = \"expression\"", |input| {
+        parse_default(input, "take")
+    }),
+    ("fn", "fn = \"path\"", |input| {
+        parse_function(input, "fn", false)
+    }),
+    ("with", "with = \"path\"", |input| {
+        parse_function(input, "with", false)
+    }),
+    ("divide_at_fn", "divide_at_fn = \"path\"", |input| {
+        parse_function(input, "divide_at_fn", true)
+    }),
+    ("with_index", "with_index = \"path\"", parse_index_function),
+    ("method", "method = \"name\"", |input| {
+        parse_method(input, "method", false)
+    }),
+    ("method_at", "method_at = \"name\"", |input| {
+        parse_method(input, "method_at", true)
+    }),
+    (
+        "dup_with",
+        "dup_with = \"path\"",
+        parse_duplication_function,
+    ),
+];
+
+/// All strategies accepted by the `divide_by` attribute, as listed in error messages.
+fn expected_strategies() -> String {
+    NAMED_STRATEGIES
+        .iter()
+        .map(|&(name, _)| name)
+        .chain(VALUED_STRATEGIES.iter().map(|&(_, written, _)| written))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Strategy with given name, for the ones without arguments.
+fn strategy_named(name: &str) -> Option<DivideBy> {
+    NAMED_STRATEGIES
+        .iter()
+        .find(|&&(named, _)| named == name)
+        .map(|(_, strategy)| strategy.clone())
+}
+
+/// Strategy of the fields without their own `divide_by` attribute:
+/// given by `#[divide_by(all = "strategy")]` on the type, `divisible` by default.
+/// Every `divide_by` attribute of the type is parsed, rejecting anything but a single `all`.
+fn fallback_strategy(input: &DeriveInput) -> syn::Result<DivideBy> {
+    let mut errors = Errors::default();
+    let mut all: Option<LitStr> = None;
+    for attribute in attributes_named(&input.attrs, "divide_by") {
+        let arguments = errors.check(
+            attribute
+                .parse_args_with(Punctuated::<MetaNameValue, Token![,]>::parse_terminated)
+                .map_err(|_| {
+                    syn::Error::new_spanned(
+                        attribute,
+                        "#[divide_by] belongs on fields, on a type it can only set the strategy \
+                         of all fields, like #[divide_by(all = \"clone\")]",
+                    )
+                }),
+        );
+        for argument in arguments.into_iter().flatten() {
+            let path = &argument.path;
+            if !path.is_ident("all") {
+                errors.push(syn::Error::new_spanned(
+                    path,
+                    format!(
+                        "unknown divide_by argument `{}` on a type, \
+                         only #[divide_by(all = \"strategy\")] is accepted there",
+                        quote!(#path)
+                    ),
+                ));
+                continue;
             }
-            fn divide(self) -> (Self, Self) {
-                #split_expression
-                (
-                    #name {
-                        #left_fields
-                    },
-                    #name{
-                        #right_fields
-                    }
-                )
+            let name = match argument.value {
+                Expr::Lit(ExprLit {
+                    lit: Lit::Str(ref name),
+                    ..
+                }) => name,
+                ref value => {
+                    errors.push(syn::Error::new_spanned(
+                        value,
+                        "the strategy of all fields must be quoted, \
+                         like #[divide_by(all = \"clone\")]",
+                    ));
+                    continue;
+                }
+            };
+            match all {
+                Some(ref first) => errors.push(syn::Error::new_spanned(
+                    &argument,
+                    format!(
+                        "duplicate #[divide_by(all = ...)], the strategy of all fields \
+                         is already given by all = \"{}\"",
+                        first.value()
+                    ),
+                )),
+                None => all = Some(name.clone()),
             }
         }
+    }
+    errors.finish()?;
+    let name = match all {
+        Some(name) => name,
+        None => return Ok(DivideBy::Divisible { length: true }),
     };
-    proc_macro::TokenStream::from(expanded)
+    strategy_named(&name.value()).ok_or_else(|| {
+        syn::Error::new(
+            name.span(),
+            format!(
+                "unknown divide_by strategy `{}` for all fields, expected one of: {}",
+                name.value(),
+                NAMED_STRATEGIES
+                    .iter()
+                    .map(|&(name, _)| name)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        )
+    })
 }
 
-/// Return argument of first attribute with given name.
-fn attributes_search(attributes: &[Attribute], searched_attribute_name: &str) -> Option<Group> {
-    attributes
-        .into_iter()
-        .find(|a| {
-            let i = syn::Ident::new(searched_attribute_name, proc_macro2::Span::call_site());
-            a.path.is_ident(i)
-        })
-        .and_then(|a| {
-            // look further into the group of arguments
-            let possible_group: Result<proc_macro2::Group, _> = syn::parse2(a.tts.clone());
-            possible_group.ok()
-        })
+/// figure out what division strategy to use for a given field,
+/// `fallback` being used when it has no `divide_by` attribute.
+/// Arguments of several `divide_by` attributes are combined.
+fn find_strategy(field: &syn::Field, fallback: &DivideBy) -> syn::Result<DivideBy> {
+    let attributes: Vec<&Attribute> = attributes_named(&field.attrs, "divide_by").collect();
+    let last = match attributes.last() {
+        Some(last) => *last,
+        // markers cannot be divided but always have a default value
+        None if is_phantom(&field.ty) => return Ok(DivideBy::Default),
+        None => return Ok(fallback.clone()),
+    };
+    let mut arguments = Vec::new();
+    for attribute in attributes {
+        let group = match attribute_group(attribute) {
+            Some(ref group) if group.delimiter() == Delimiter::Parenthesis => group.clone(),
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    attribute,
+                    "#[divide_by] requires a strategy in parentheses, like #[divide_by(clone)]",
+                ))
+            }
+        };
+        if group.stream().is_empty() {
+            return Err(syn::Error::new(
+                group.span(),
+                format!(
+                    "missing divide_by strategy, expected one of: {}",
+                    expected_strategies()
+                ),
+            ));
+        }
+        let parsed =
+            Punctuated::<StrategyArgument, Token![,]>::parse_terminated.parse2(group.stream())?;
+        arguments.extend(parsed.into_iter().map(|argument| (argument, attribute)));
+    }
+    combine_strategy(arguments, last)
 }
 
-/// Extract power attribute's value
-fn power_type(attributes: &[Attribute]) -> Group {
-    attributes_search(attributes, "power").expect("missing power attribute")
+/// One argument of a field `divide_by` attribute.
+enum StrategyArgument {
+    /// A strategy given by name like `clone` or `"clone"`, or `ratio = numerator/denominator`,
+    /// with the way it was written for error messages
+    Strategy(DivideBy, String),
+    /// `fn = "path"` (or `with = "path"`) and `divide_at_fn = "path"`,
+    /// `method = "name"` and `method_at = "name"`
+    Function {
+        at_index: bool,
+        splitter: Splitter,
+        written: String,
+    },
+    /// `no_length`, excluding a divided field from the base length (with its span for errors)
+    NoLength(Span),
 }
 
-#[proc_macro_derive(DivisibleIntoBlocks, attributes(divide_by))]
-pub fn derive_divisible_into_blocks(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
-    let input = parse_macro_input!(input as DeriveInput);
-    let name = input.ident;
-    let generics = input.generics;
-    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+impl Parse for StrategyArgument {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        // `"clone"` is accepted as well as `clone`
+        let (string, span) = if input.peek(Token![fn]) {
+            ("fn".to_owned(), input.parse::<Token![fn]>()?.span)
+        } else if input.peek(LitStr) {
+            let name: LitStr = input.parse()?;
+            (name.value(), name.span())
+        } else {
+            let name: Ident = input.parse()?;
+            (name.to_string(), name.span())
+        };
+        if input.peek(Token![=]) {
+            return match VALUED_STRATEGIES
+                .iter()
+                .find(|&&(name, _, _)| name == string)
+            {
+                Some(&(_, _, parse)) => parse(input),
+                None => Err(syn::Error::new(
+                    span,
+                    format!(
+                        "unknown divide_by argument `{}`, expected one of: {}",
+                        string,
+                        VALUED_STRATEGIES
+                            .iter()
+                            .map(|&(_, written, _)| written)
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ),
+                )),
+            };
+        }
+        if string == "no_length" {
+            return Ok(StrategyArgument::NoLength(span));
+        }
+        strategy_named(&string)
+            .map(|strategy| StrategyArgument::Strategy(strategy, string.clone()))
+            .ok_or_else(|| {
+                syn::Error::new(
+                    span,
+                    match suggest_strategy(&string) {
+                        Some(suggestion) => format!(
+                            "unknown divide_by strategy `{}`, did you mean `{}`?",
+                            string, suggestion
+                        ),
+                        None => format!(
+                            "unknown divide_by strategy `{}`, expected one of: {}",
+                            string,
+                            expected_strategies()
+                        ),
+                    },
+                )
+            })
+    }
+}
 
-    // split into tuple of couples (left and right)
-    let split_expression = generate_split_into_blocks_declarations(&input.data);
-    // move tuple into fields of split structure
-    let left_fields = generate_fields(&input.data, 0);
-    let right_fields = generate_fields(&input.data, 1);
+/// Parse the `= numerator/denominator` part of a `ratio` argument.
+fn parse_ratio(input: ParseStream) -> syn::Result<StrategyArgument> {
+    input.parse::<Token![=]>()?;
+    let numerator: LitInt = input.parse()?;
+    input.parse::<Token![/]>()?;
+    let denominator: LitInt = input.parse()?;
+    let numerator_span = numerator.span();
+    let (numerator, denominator) = (numerator.base10_parse()?, denominator.base10_parse()?);
+    if denominator == 0 || numerator > denominator {
+        return Err(syn::Error::new(
+            numerator_span,
+            "a divide_by ratio must be a fraction between 0 and 1, like ratio = 3/4",
+        ));
+    }
+    let ratio = DivideBy::Ratio {
+        numerator,
+        denominator,
+        length: true,
+    };
+    Ok(StrategyArgument::Strategy(
+        ratio,
+        format!("ratio = {}/{}", numerator, denominator),
+    ))
+}
 
-    let expanded = quote! {
-        impl #impl_generics DivisibleIntoBlocks for #name #ty_generics #where_clause {
-            fn divide_at(self, index: usize) -> (Self, Self) {
-                #split_expression
-                (
-                    #name {
-                        #left_fields
-                    },
-                    #name{
-                        #right_fields
+/// Parse the `= "expression"` part of a `default`, `default_left` or `take` argument.
+fn parse_default(input: ParseStream, key: &str) -> syn::Result<StrategyArgument> {
+    input.parse::<Token![=]>()?;
+    let value: LitStr = input.parse()?;
+    let expression = syn::parse_str::<Expr>(&value.value()).map_err(|error| {
+        syn::Error::new(
+            value.span(),
+            format!(
+                "invalid divide_by {} expression `{}`: {}",
+                key,
+                value.value(),
+                error
+            ),
+        )
+    })?;
+    let strategy = if key == "default_left" {
+        DivideBy::DefaultLeftValue(expression)
+    } else {
+        DivideBy::DefaultValue(expression)
+    };
+    Ok(StrategyArgument::Strategy(
+        strategy,
+        format!("{} = \"{}\"", key, value.value()),
+    ))
+}
+
+/// Parse the `= "path"` part of a `fn`, `with` or `divide_at_fn` argument.
+fn parse_function(input: ParseStream, key: &str, at_index: bool) -> syn::Result<StrategyArgument> {
+    let (path, written) = parse_path(input, key)?;
+    Ok(StrategyArgument::Function {
+        at_index,
+        splitter: Splitter::Function(path),
+        written,
+    })
+}
+
+/// Parse the `= "name"` part of a `method` or `method_at` argument.
+fn parse_method(input: ParseStream, key: &str, at_index: bool) -> syn::Result<StrategyArgument> {
+    input.parse::<Token![=]>()?;
+    let name: LitStr = input.parse()?;
+    let method = name.parse().map_err(|_| {
+        syn::Error::new(
+            name.span(),
+            format!(
+                "invalid divide_by {} `{}`, expected a method name like \"split\"",
+                key,
+                name.value()
+            ),
+        )
+    })?;
+    Ok(StrategyArgument::Function {
+        at_index,
+        splitter: Splitter::Method(method),
+        written: format!("{} = \"{}\"", key, name.value()),
+    })
+}
+
+/// Parse the `= "path"` part of a `dup_with` argument.
+fn parse_duplication_function(input: ParseStream) -> syn::Result<StrategyArgument> {
+    let (path, written) = parse_path(input, "dup_with")?;
+    Ok(StrategyArgument::Strategy(DivideBy::DupFn(path), written))
+}
+
+/// Parse the `= "path"` part of a `with_index` argument,
+/// the function being used by both `divide` and `divide_at`.
+fn parse_index_function(input: ParseStream) -> syn::Result<StrategyArgument> {
+    let (path, written) = parse_path(input, "with_index")?;
+    Ok(StrategyArgument::Strategy(DivideBy::IndexFn(path), written))
+}
+
+/// Parse the `= "path"` part of an argument given by `key`,
+/// returning the path with the way it was written.
+fn parse_path(input: ParseStream, key: &str) -> syn::Result<(Path, String)> {
+    input.parse::<Token![=]>()?;
+    let path: LitStr = input.parse()?;
+    let function = path.parse().map_err(|error| {
+        syn::Error::new(
+            path.span(),
+            format!(
+                "invalid divide_by {} function `{}`: {}, expected a path like \"path::to::split\"",
+                key,
+                path.value(),
+                error
+            ),
+        )
+    })?;
+    Ok((function, format!("{} = \"{}\"", key, path.value())))
+}
+
+/// Combine the arguments of the `divide_by` attributes of a field (each with its attribute):
+/// exactly one strategy (custom functions counting as one), possibly with `no_length`.
+fn combine_strategy(
+    arguments: Vec<(StrategyArgument, &Attribute)>,
+    last: &Attribute,
+) -> syn::Result<DivideBy> {
+    // the strategy with the way it was first written and where
+    let mut strategy: Option<(DivideBy, String, &Attribute)> = None;
+    let mut no_length = None;
+    for (argument, attribute) in arguments {
+        let (argument, written) = match argument {
+            StrategyArgument::NoLength(span) => {
+                no_length = Some(span);
+                continue;
+            }
+            StrategyArgument::Function {
+                at_index,
+                splitter,
+                written,
+            } => {
+                // both functions (or methods) are given by separate arguments
+                if let Some((DivideBy::CustomFn { divide, divide_at }, _, _)) = strategy.as_mut() {
+                    let target = if at_index { divide_at } else { divide };
+                    if target.is_none() {
+                        *target = Some(splitter);
+                        continue;
                     }
-                )
+                }
+                let splitter = Some(splitter);
+                let custom = if at_index {
+                    DivideBy::CustomFn {
+                        divide: None,
+                        divide_at: splitter,
+                    }
+                } else {
+                    DivideBy::CustomFn {
+                        divide: splitter,
+                        divide_at: None,
+                    }
+                };
+                (custom, written)
+            }
+            StrategyArgument::Strategy(strategy, written) => (strategy, written),
+        };
+        if let Some((_, ref first, first_attribute)) = strategy {
+            let conflict = syn::Error::new_spanned(
+                attribute,
+                format!(
+                    "conflicting divide_by strategies `{}` and `{}`: strategies \
+                     (like `clone`, `default`, `copy` and `divisible`) are mutually exclusive, \
+                     a field can only have one of them (possibly along with modifiers like `no_length`)",
+                    first, written
+                ),
+            );
+            if std::ptr::eq(first_attribute, attribute) {
+                return Err(conflict);
+            }
+            // given by separate attributes: also point at the first one
+            let mut error = syn::Error::new_spanned(
+                first_attribute,
+                format!("divide_by strategy `{}` first given here", first),
+            );
+            error.combine(conflict);
+            return Err(error);
+        }
+        strategy = Some((argument, written, attribute));
+    }
+    let (strategy, _, _) = strategy.ok_or_else(|| {
+        syn::Error::new_spanned(
+            last,
+            format!(
+                "missing divide_by strategy, expected one of: {}",
+                expected_strategies()
+            ),
+        )
+    })?;
+    match (no_length, strategy) {
+        (None, strategy) => Ok(strategy),
+        (Some(_), DivideBy::Divisible { .. }) => Ok(DivideBy::Divisible { length: false }),
+        (
+            Some(_),
+            DivideBy::Ratio {
+                numerator,
+                denominator,
+                ..
+            },
+        ) => Ok(DivideBy::Ratio {
+            numerator,
+            denominator,
+            length: false,
+        }),
+        (Some(no_length), _) => Err(syn::Error::new(
+            no_length,
+            "no_length only applies to the divisible, half and ratio strategies, \
+             like #[divide_by(divisible, no_length)] or #[divide_by(half, no_length)]",
+        )),
+    }
+}
+
+/// Find the strategy closest to a misspelled one, if any is close enough.
+fn suggest_strategy(unknown: &str) -> Option<&'static str> {
+    let unknown = unknown.to_lowercase();
+    NAMED_STRATEGIES
+        .iter()
+        .map(|&(strategy, _)| (edit_distance(&unknown, strategy), strategy))
+        .filter(|&(distance, strategy)| distance <= (strategy.len() / 3).max(1))
+        .min()
+        .map(|(_, strategy)| strategy)
+}
+
+/// Edit distance between two strings, swapping two adjacent characters
+/// counting as a single edit.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut distances = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in distances.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    distances[0] = (0..=b.len()).collect();
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = if a[i - 1] == b[j - 1] { 0 } else { 1 };
+            let mut distance = (distances[i - 1][j - 1] + cost)
+                .min(distances[i - 1][j] + 1)
+                .min(distances[i][j - 1] + 1);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(distances[i - 2][j - 2] + 1);
+            }
+            distances[i][j] = distance;
+        }
+    }
+    distances[a.len()][b.len()]
+}
+
+/// Generics of the derived impl: the ones of the input with the bounds required
+/// by the fields division strategies added to the where clause, unless the
+/// container is marked with `#[divisible(no_bounds)]` or gives its own
+/// predicates with `#[divisible(bound = "...")]`.
+/// Fields cloned, copied, defaulted or ignored require the corresponding std trait
+/// and divided fields the derived `division_trait` (and `DivisibleIntoBlocks` for a
+/// `ratio`), but only when their type depends on a type parameter: other bounds
+/// would be trivially checked and reported on the whole derive, instead of on the
+/// field by the spanned generated code.
+fn bounded_generics(plan: &Plan, division_trait: &Path) -> Generics {
+    if let Some(ref generics) = plan.user_generics {
+        return generics.clone();
+    }
+    let parameters = type_parameters(&plan.input.generics);
+    let root = &plan.root;
+    let mut predicates: Vec<WherePredicate> = Vec::new();
+    for field in plan.fields().filter(|f| !is_phantom(&f.field.ty)) {
+        let ty = &field.field.ty;
+        if !uses_parameters(ty, &parameters) {
+            continue;
+        }
+        match field.strategy {
+            DivideBy::Copy => predicates.push(field_bound(ty, quote!(::core::marker::Copy))),
+            DivideBy::Clone | DivideBy::Share => {
+                predicates.push(field_bound(ty, quote!(::core::clone::Clone)))
+            }
+            DivideBy::Default | DivideBy::DefaultLeft | DivideBy::Ignore => {
+                predicates.push(field_bound(ty, quote!(::core::default::Default)))
+            }
+            // splitting at a key requires cloning it
+            DivideBy::BtreeSplit => {
+                if let Some(key) = first_type_argument(ty) {
+                    predicates.push(field_bound(
+                        key,
+                        quote!(::core::clone::Clone + ::core::cmp::Ord),
+                    ))
+                }
+            }
+            DivideBy::Divisible { .. } => predicates.push(parse_quote!(#ty: #division_trait)),
+            // `divide` cuts the field with `divide_at`
+            DivideBy::Ratio { .. } => {
+                predicates.push(parse_quote!(#ty: #division_trait));
+                predicates.push(parse_quote!(#ty: #root::DivisibleIntoBlocks));
             }
+            _ => (),
         }
+    }
+    add_predicates(&plan.input.generics, predicates)
+}
+
+/// Generics of the input with the bounds given by the user,
+/// if marked with `#[divisible(no_bounds)]` or `#[divisible(bound = "...")]`.
+fn user_bounded_generics(
+    input: &DeriveInput,
+    options: &DivisibleOptions,
+) -> syn::Result<Option<Generics>> {
+    let mut generics = input.generics.clone();
+    match (options.flag("no_bounds"), options.string("bound")) {
+        (Some(_), Some(bound)) => {
+            return Err(syn::Error::new(
+                bound.span(),
+                "#[divisible(bound = \"...\")] conflicts with #[divisible(no_bounds)]",
+            ))
+        }
+        (Some(_), None) => return Ok(Some(generics)),
+        (None, Some(bound)) => {
+            let predicates = Punctuated::<WherePredicate, Token![,]>::parse_terminated
+                .parse_str(&bound.value())
+                .map_err(|error| syn::Error::new(bound.span(), error))?;
+            generics.make_where_clause().predicates.extend(predicates);
+            return Ok(Some(generics));
+        }
+        (None, None) => (),
+    }
+    Ok(None)
+}
+
+/// Names of the type parameters.
+fn type_parameters(generics: &Generics) -> Vec<Ident> {
+    generics
+        .type_params()
+        .map(|param| param.ident.clone())
+        .collect()
+}
+
+/// Add given predicates to the where clause of given generics.
+fn add_predicates(generics: &Generics, predicates: Vec<WherePredicate>) -> Generics {
+    let mut generics = generics.clone();
+    // do not repeat predicates, be they from the user or from another field
+    let where_clause = generics.make_where_clause();
+    let mut existing: Vec<String> = where_clause
+        .predicates
+        .iter()
+        .map(|predicate| quote!(#predicate).to_string())
+        .collect();
+    for predicate in predicates {
+        let key = quote!(#predicate).to_string();
+        if !existing.contains(&key) {
+            existing.push(key);
+            where_clause.predicates.push(predicate);
+        }
+    }
+    generics
+}
+
+/// Predicate bounding given field type by given trait,
+/// spanned on the field type so that unsatisfied bounds are reported on the field.
+fn field_bound(ty: &Type, bound: TokenStream) -> WherePredicate {
+    syn::parse2(quote_spanned!(ty.span()=> #ty: #bound))
+        .expect("a type bounded by a trait is a valid predicate")
+}
+
+/// Does given type mention any of the given type parameters.
+fn uses_parameters(ty: &Type, parameters: &[Ident]) -> bool {
+    struct Finder<'p> {
+        parameters: &'p [Ident],
+        found: bool,
+    }
+    impl<'ast, 'p> Visit<'ast> for Finder<'p> {
+        fn visit_path(&mut self, path: &'ast Path) {
+            // a parameter can only be a lone identifier (or the start of `T::Assoc`)
+            if path.leading_colon.is_none() {
+                if let Some(first) = path.segments.first() {
+                    let ident = &first.ident;
+                    self.found |= self.parameters.iter().any(|p| p == ident);
+                }
+            }
+            visit::visit_path(self, path);
+        }
+    }
+    let mut finder = Finder {
+        parameters,
+        found: false,
     };
-    proc_macro::TokenStream::from(expanded)
+    finder.visit_type(ty);
+    finder.found
 }
 
-#[proc_macro_derive(DivisibleAtIndex, attributes(divide_by))]
-pub fn derive_divisible_at_index(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
-    let input = parse_macro_input!(input as DeriveInput);
-    let name = input.ident;
-    let generics = input.generics;
-    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
-    let expanded = quote! {
-        impl #impl_generics DivisibleAtIndex for #name #ty_generics #where_clause {}
+/// The first type argument of a type, like `K` in `BTreeMap<K, V>`.
+fn first_type_argument(ty: &Type) -> Option<&Type> {
+    let segment = match *ty {
+        Type::Path(ref path) => path.path.segments.last()?,
+        _ => return None,
     };
-    proc_macro::TokenStream::from(expanded)
+    match segment.arguments {
+        syn::PathArguments::AngleBracketed(ref arguments) => {
+            arguments.args.iter().find_map(|argument| match *argument {
+                syn::GenericArgument::Type(ref ty) => Some(ty),
+                _ => None,
+            })
+        }
+        _ => None,
+    }
 }
 
-/// What strategy to apply when dividing a field.
-#[derive(Debug, PartialEq, Eq)]
-enum DivideBy {
-    /// Clone the field
-    Clone,
-    /// Take a default value on right side and move on the left
-    Default,
-    /// Divide using divisible
-    Divisible,
+/// Does given type mention one of the given types, anywhere in its paths
+/// (like `Tree` in `Vec<Tree>` or `crate::Tree`).
+/// Only bare names and `crate::` paths count: `inner::Iter` is another type than `Iter`.
+fn mentions_types(ty: &Type, names: &[Ident]) -> bool {
+    struct Finder<'n> {
+        names: &'n [Ident],
+        found: bool,
+    }
+    impl<'ast, 'n> Visit<'ast> for Finder<'n> {
+        fn visit_path(&mut self, path: &'ast Path) {
+            let segments: Vec<&Ident> = path.segments.iter().map(|s| &s.ident).collect();
+            let name = match segments[..] {
+                [name] if path.leading_colon.is_none() => Some(name),
+                [root, name] if path.leading_colon.is_none() && root == "crate" => Some(name),
+                _ => None,
+            };
+            self.found |= name.is_some_and(|name| self.names.contains(name));
+            visit::visit_path(self, path);
+        }
+    }
+    let mut finder = Finder {
+        names,
+        found: false,
+    };
+    finder.visit_type(ty);
+    finder.found
 }
 
-/// figure out what division strategy to use for a given field.
-fn find_strategy(field: &syn::Field) -> DivideBy {
-    attributes_search(&field.attrs, "divide_by")
-        .map(|group| {
-            let string = group
-                .stream()
-                .into_iter()
-                .map(|s| s.to_string())
-                .collect::<String>();
-            match string.as_ref() {
-                "clone" => DivideBy::Clone,
-                "default" => DivideBy::Default,
-                _ => DivideBy::Divisible,
-            }
+/// Return the path building each possible shape of the data
+/// (the struct itself or each enum variant) together with its fields.
+/// Variants also come with their attributes. Unions have no shape.
+fn shapes<'a>(name: &Ident, data: &'a Data) -> Vec<(TokenStream, &'a Fields, &'a [Attribute])> {
+    match *data {
+        Data::Struct(ref data) => vec![(quote!(#name), &data.fields, &[])],
+        Data::Enum(ref data) => data
+            .variants
+            .iter()
+            .map(|v| {
+                let variant = &v.ident;
+                (quote!(#name::#variant), &v.fields, &v.attrs[..])
+            })
+            .collect(),
+        // rejected with a located error by `reject_unions` (or `Merge`)
+        Data::Union(_) => Vec::new(),
+    }
+}
+
+/// Variables binding each field when matching on self, called
+/// `__derive_divisible_field_0`, `__derive_divisible_field_1`... so that they
+/// cannot shadow the functions and constants used in `divide_by` attributes.
+fn bindings(fields: &Fields) -> Vec<Ident> {
+    (0..fields.len())
+        .map(|i| {
+            Ident::new(
+                &format!("__derive_divisible_field_{}", i),
+                Span::call_site(),
+            )
+        })
+        .collect()
+}
+
+/// Names (or indices for unnamed fields) of all fields.
+fn members(fields: &Fields) -> Vec<Member> {
+    fields
+        .iter()
+        .enumerate()
+        .map(|(i, f)| match f.ident {
+            Some(ref ident) => Member::Named(ident.clone()),
+            None => Member::Unnamed(Index::from(i)),
+        })
+        .collect()
+}
+
+/// Names of the variables storing the (left, right) couples of split fields,
+/// prefixed like the fields bindings.
+fn split_variables(fields: &Fields) -> Vec<Ident> {
+    (0..fields.len())
+        .map(|i| {
+            Ident::new(
+                &format!("__derive_divisible_split_field_{}", i),
+                Span::call_site(),
+            )
         })
-        .unwrap_or(DivideBy::Divisible)
+        .collect()
 }
 
-/// Fill fields of target struct from content of tuple storing
-/// split fields.
+/// Fill fields of target struct from the variables storing split fields.
 /// Index indicate if we fill left or right structure.
-fn generate_fields(data: &Data, index: usize) -> TokenStream {
+fn generate_fields(fields: &Fields, index: usize) -> TokenStream {
     let index = syn::Index::from(index);
-    match *data {
-        Data::Struct(ref data) => match data.fields {
-            Fields::Named(ref fields) => {
-                let recurse = fields.named.iter().enumerate().map(|(i, f)| {
-                    let i = syn::Index::from(i);
-                    let name = &f.ident;
-                    quote! {
-                        #name: (split_fields.#i).#index
-                    }
-                });
-                quote! {
-                    #(#recurse, )*
-                }
-            }
-            Fields::Unnamed(ref fields) => {
-                let recurse = fields.unnamed.iter().enumerate().map(|(i, _)| {
-                    let i = syn::Index::from(i);
-                    quote! {
-                        (split_fields.#i).#index
-                    }
-                });
-                quote! {
-                    #(#recurse, )*
-                }
+    let recurse = members(fields)
+        .into_iter()
+        .zip(split_variables(fields))
+        .map(|(member, variable)| {
+            quote! {
+                #member: #variable.#index
             }
-            Fields::Unit => quote!(),
-        },
-        Data::Enum(_) | Data::Union(_) => unimplemented!(),
+        });
+    quote! {
+        #(#recurse, )*
     }
 }
 
-/// Generate the function splitting the divisible
-fn generate_split_declarations(data: &Data) -> TokenStream {
-    match *data {
-        Data::Struct(ref data) => match data.fields {
-            Fields::Named(ref fields) => {
-                let recurse = fields.named.iter().map(|f| {
-                    let name = &f.ident;
-                    match find_strategy(&f) {
-                        DivideBy::Clone => {
-                            quote! {
-                                (self.#name.clone(), self.#name)
-                            }
-                        }
-                        DivideBy::Default => {
-                            quote! {
-                                (self.#name, Default::default())
-                            }
-                        }
-                        DivideBy::Divisible => {
-                            quote! {
-                                self.#name.divide()
-                            }
-                        }
-                    }
-                });
-                quote! {
-                    let split_fields = (#(#recurse, )*);
-                }
+/// Generate the body of `divide` or `divide_at`: match self, split all fields
+/// and rebuild the same shape on both sides.
+/// If a variant is marked as the empty one, exhausted data is not split
+/// but kept on the left with the empty variant on the right.
+fn generate_divide_expression(plan: &Plan, division: Division) -> syn::Result<TokenStream> {
+    let root = &plan.root;
+    let exhausted = empty_variant(plan)?.map(|empty| {
+        let condition = match division {
+            Division::Divide => quote! {
+                #root::Divisible::base_length(&self) == 0
+            },
+            Division::DivideAt => quote! {{
+                let length = #root::Divisible::base_length(&self);
+                length == 0 || __derive_divisible_index == length
+            }},
+        };
+        quote! {
+            if #condition {
+                return (self, #empty);
             }
-            Fields::Unnamed(ref fields) => {
-                let recurse = fields.unnamed.iter().enumerate().map(|(i, f)| {
-                    let i = syn::Index::from(i);
-                    match find_strategy(&f) {
-                        DivideBy::Clone => {
-                            quote! {
-                                (self.#i.clone(), self.#i)
-                            }
-                        }
-                        DivideBy::Default => {
-                            quote! {
-                                (self.#i, Default::default())
-                            }
-                        }
-                        DivideBy::Divisible => {
-                            quote! {
-                                self.#i.divide()
-                            }
-                        }
+        }
+    });
+    // `with_index` functions cut at the middle in `divide`, computed before moving out fields
+    let middle = match division {
+        Division::Divide
+            if plan
+                .fields()
+                .any(|field| matches!(field.strategy, DivideBy::IndexFn(_))) =>
+        {
+            Some(quote! {
+                let __derive_divisible_middle = #root::Divisible::base_length(&self) / 2;
+            })
+        }
+        _ => None,
+    };
+    let mut arms = Vec::new();
+    for shape in &plan.shapes {
+        let (path, fields) = (&shape.path, shape.fields);
+        if let Fields::Unit = *fields {
+            // nothing to divide
+            arms.push(quote! {
+                #path => (#path, #path),
+            });
+            continue;
+        }
+        let bindings = bindings(fields);
+        let members = members(fields);
+        // split into couples (left and right)
+        let split_expression = generate_split_declarations(root, shape, &bindings, division)?;
+        // move couples into fields of split structure
+        let left_fields = generate_fields(fields, 0);
+        let right_fields = generate_fields(fields, 1);
+        arms.push(quote! {
+            #path { #(#members: #bindings),* } => {
+                #split_expression
+                (
+                    #path {
+                        #left_fields
+                    },
+                    #path {
+                        #right_fields
                     }
-                });
-                quote! {
-                    let split_fields = (#(#recurse, )*);
-                }
+                )
             }
-            Fields::Unit => quote!(),
-        },
-        Data::Enum(_) | Data::Union(_) => unimplemented!(),
+        });
     }
+    Ok(quote! {
+        #exhausted
+        #middle
+        match self {
+            #(#arms)*
+        }
+    })
 }
 
-/// compute base length of the structure
-fn generate_len_expression(data: &Data) -> TokenStream {
-    match *data {
-        Data::Struct(ref data) => {
-            match data.fields {
-                Fields::Named(ref fields) => {
-                    let recurse = fields
-                        .named
-                        .iter()
-                        .filter(|f| find_strategy(f) == DivideBy::Divisible)
-                        .map(|f| {
-                            let name = &f.ident;
-                            quote! {::std::iter::once(self.#name.base_length())}
-                        });
+/// Find the variant marked with `#[divisible(empty)]` if any.
+fn empty_variant<'a>(plan: &'a Plan) -> syn::Result<Option<&'a TokenStream>> {
+    let mut empty = None;
+    for shape in &plan.shapes {
+        let marker = match shape.options.flag("empty") {
+            Some(marker) => marker,
+            None => continue,
+        };
+        if !shape.fields.is_empty() {
+            return Err(syn::Error::new(
+                marker,
+                "#[divisible(empty)] only applies to unit variants",
+            ));
+        }
+        if empty.is_some() {
+            return Err(syn::Error::new(
+                marker,
+                "only one variant can be marked with #[divisible(empty)]",
+            ));
+        }
+        empty = Some(&shape.path);
+    }
+    Ok(empty)
+}
+
+/// Generate the declarations splitting all fields (bound to given variables),
+/// one variable per field.
+fn generate_split_declarations(
+    root: &Path,
+    shape: &Shape,
+    bindings: &[Ident],
+    division: Division,
+) -> syn::Result<TokenStream> {
+    let recurse = shape
+        .plans
+        .iter()
+        .zip(bindings)
+        .map(|(plan, binding)| {
+            // spanned on the field type so that missing impls are reported on the field
+            let ty = &plan.field.ty;
+            let span = ty.span();
+            Ok(match plan.strategy {
+                DivideBy::Clone | DivideBy::Share => {
+                    quote_spanned! {span=>
+                        (<#ty as ::core::clone::Clone>::clone(&#binding), #binding)
+                    }
+                }
+                DivideBy::ArcClone => {
                     quote! {
-                        ::std::iter::once(std::usize::MAX)#(.chain(#recurse))*.min().unwrap()
+                        (::std::sync::Arc::clone(&#binding), #binding)
                     }
                 }
-                Fields::Unnamed(ref fields) => {
-                    let recurse = fields
-                        .unnamed
-                        .iter()
-                        .enumerate()
-                        .filter(|&(_, f)| find_strategy(f) == DivideBy::Divisible)
-                        .map(|(i, _)| {
-                            quote! {::std::iter::once(self.#i.base_length())}
-                        });
+                DivideBy::RcClone => {
                     quote! {
-                        ::std::iter::once(std::usize::MAX)#(.chain(#recurse))*.min().unwrap()
+                        (::std::rc::Rc::clone(&#binding), #binding)
                     }
                 }
-                Fields::Unit => {
-                    // Unit structs have an infinite base length
-                    quote!(std::usize::MAX)
+                DivideBy::MutexClone => {
+                    // the annotation reports fields which are not shared mutexes
+                    quote_spanned! {span=> {
+                        let __derive_divisible_shared: ::std::sync::Arc<::std::sync::Mutex<_>> =
+                            #binding;
+                        (
+                            ::std::sync::Arc::clone(&__derive_divisible_shared),
+                            __derive_divisible_shared,
+                        )
+                    }}
                 }
-            }
-        }
-        Data::Enum(_) | Data::Union(_) => unimplemented!(),
-    }
-}
-
-/// Generate the function splitting the divisible
-fn generate_split_into_blocks_declarations(data: &Data) -> TokenStream {
-    match *data {
-        Data::Struct(ref data) => match data.fields {
-            Fields::Named(ref fields) => {
-                let recurse = fields.named.iter().map(|f| {
-                    let name = &f.ident;
-                    match find_strategy(&f) {
-                        DivideBy::Clone => {
-                            quote! {
-                                (self.#name.clone(), self.#name)
-                            }
+                DivideBy::Copy => {
+                    // copying through a `Copy` bound reports non `Copy` types on the field
+                    quote_spanned! {span=> {
+                        fn __derive_divisible_copy<T: ::core::marker::Copy>(value: T) -> (T, T) {
+                            (value, value)
                         }
-                        DivideBy::Default => {
-                            quote! {
-                                (self.#name, Default::default())
+                        __derive_divisible_copy::<#ty>(#binding)
+                    }}
+                }
+                DivideBy::Default => {
+                    quote_spanned! {span=>
+                        (#binding, ::core::default::Default::default())
+                    }
+                }
+                DivideBy::DefaultLeft => {
+                    quote_spanned! {span=>
+                        (::core::default::Default::default(), #binding)
+                    }
+                }
+                DivideBy::DefaultLeftValue(ref expression) => {
+                    quote_spanned! {span=>
+                        (#expression, #binding)
+                    }
+                }
+                DivideBy::DefaultValue(ref expression) => {
+                    quote_spanned! {span=>
+                        (#binding, #expression)
+                    }
+                }
+                DivideBy::Take => {
+                    quote_spanned! {span=>
+                        (#binding, ::core::option::Option::None)
+                    }
+                }
+                DivideBy::EmptyVec => {
+                    quote_spanned! {span=>
+                        (#binding, ::std::vec::Vec::new())
+                    }
+                }
+                DivideBy::SplitOff => {
+                    let index = match division {
+                        Division::Divide => quote!(__derive_divisible_left.len() / 2),
+                        Division::DivideAt => quote!(__derive_divisible_index),
+                    };
+                    quote_spanned! {span=> {
+                        let mut __derive_divisible_left = #binding;
+                        let __derive_divisible_right = __derive_divisible_left.split_off(#index);
+                        (__derive_divisible_left, __derive_divisible_right)
+                    }}
+                }
+                DivideBy::DequeSplit => {
+                    let index = match division {
+                        Division::Divide => quote!(__derive_divisible_left.len() / 2),
+                        Division::DivideAt => quote!(__derive_divisible_index),
+                    };
+                    quote_spanned! {span=> {
+                        let mut __derive_divisible_left = #binding;
+                        let __derive_divisible_right: #ty =
+                            __derive_divisible_left.drain(#index..).collect();
+                        (__derive_divisible_left, __derive_divisible_right)
+                    }}
+                }
+                DivideBy::BtreeSplit => {
+                    let index = match division {
+                        Division::Divide => quote!(__derive_divisible_left.len() / 2),
+                        Division::DivideAt => quote!(__derive_divisible_index),
+                    };
+                    // everything goes left when cutting after the last entry
+                    quote_spanned! {span=> {
+                        let mut __derive_divisible_left: #ty = #binding;
+                        let __derive_divisible_key = __derive_divisible_left
+                            .keys()
+                            .nth(#index)
+                            .cloned();
+                        let __derive_divisible_right = match __derive_divisible_key {
+                            ::core::option::Option::Some(key) => {
+                                __derive_divisible_left.split_off(&key)
                             }
+                            ::core::option::Option::None => ::core::default::Default::default(),
+                        };
+                        (__derive_divisible_left, __derive_divisible_right)
+                    }}
+                }
+                DivideBy::RangeSplit => {
+                    let index = match division {
+                        Division::Divide => quote! {
+                            ::core::iter::ExactSizeIterator::len(&__derive_divisible_range) / 2
+                        },
+                        Division::DivideAt => quote!(__derive_divisible_index),
+                    };
+                    // everything goes left when cutting after the end
+                    quote_spanned! {span=> {
+                        let __derive_divisible_range: ::core::ops::Range<usize> = #binding;
+                        let __derive_divisible_middle = ::core::cmp::min(
+                            __derive_divisible_range.start.saturating_add(#index),
+                            __derive_divisible_range.end,
+                        );
+                        (
+                            __derive_divisible_range.start..__derive_divisible_middle,
+                            __derive_divisible_middle..__derive_divisible_range.end,
+                        )
+                    }}
+                }
+                DivideBy::StringChars => {
+                    let index = match division {
+                        Division::Divide => quote!(__derive_divisible_left.chars().count() / 2),
+                        Division::DivideAt => quote!(__derive_divisible_index),
+                    };
+                    // indices are in chars, everything goes left when cutting after the last one
+                    quote_spanned! {span=> {
+                        let mut __derive_divisible_left: #ty = #binding;
+                        let __derive_divisible_byte = __derive_divisible_left
+                            .char_indices()
+                            .nth(#index)
+                            .map_or(__derive_divisible_left.len(), |(byte, _)| byte);
+                        let __derive_divisible_right =
+                            __derive_divisible_left.split_off(__derive_divisible_byte);
+                        (__derive_divisible_left, __derive_divisible_right)
+                    }}
+                }
+                DivideBy::Ignore => {
+                    quote_spanned! {span=>
+                        (
+                            ::core::default::Default::default(),
+                            ::core::default::Default::default(),
+                        )
+                    }
+                }
+                DivideBy::Divisible { .. } => match division {
+                    Division::Divide => quote_spanned! {span=>
+                        <#ty as #root::Divisible>::divide(#binding)
+                    },
+                    Division::DivideAt => quote_spanned! {span=>
+                        <#ty as #root::DivisibleIntoBlocks>::divide_at(
+                            #binding,
+                            __derive_divisible_index,
+                        )
+                    },
+                },
+                DivideBy::Ratio {
+                    numerator,
+                    denominator,
+                    ..
+                } => match division {
+                    // length * numerator / denominator, without overflowing
+                    Division::Divide => {
+                        let numerator = Literal::u64_unsuffixed(numerator);
+                        let denominator = Literal::u64_unsuffixed(denominator);
+                        quote_spanned! {span=> {
+                            let __derive_divisible_length =
+                                <#ty as #root::Divisible>::base_length(&#binding);
+                            <#ty as #root::DivisibleIntoBlocks>::divide_at(
+                                #binding,
+                                __derive_divisible_length / #denominator * #numerator
+                                    + __derive_divisible_length % #denominator * #numerator
+                                        / #denominator,
+                            )
+                        }}
+                    }
+                    Division::DivideAt => quote_spanned! {span=>
+                        <#ty as #root::DivisibleIntoBlocks>::divide_at(
+                            #binding,
+                            __derive_divisible_index,
+                        )
+                    },
+                },
+                DivideBy::DupFn(ref function) => quote! {
+                    (#function(&#binding), #binding)
+                },
+                DivideBy::IndexFn(ref function) => {
+                    let index = match division {
+                        Division::Divide => quote!(__derive_divisible_middle),
+                        Division::DivideAt => quote!(__derive_divisible_index),
+                    };
+                    quote! {
+                        #function(#binding, #index)
+                    }
+                }
+                DivideBy::CustomFn {
+                    ref divide,
+                    ref divide_at,
+                } => {
+                    match (division, divide.as_ref(), divide_at.as_ref()) {
+                        (Division::Divide, Some(splitter), _) => splitter.call(binding, None, span),
+                        (Division::DivideAt, _, Some(splitter)) => {
+                            splitter.call(binding, Some(quote!(__derive_divisible_index)), span)
                         }
-                        DivideBy::Divisible => {
-                            quote! {
-                                self.#name.divide_at(index)
-                            }
+                        (Division::Divide, None, _) => return Err(syn::Error::new_spanned(
+                            plan.field,
+                            "dividing this field requires a #[divide_by(fn = \"path\")] function \
+                             or a #[divide_by(method = \"name\")] method",
+                        )),
+                        (Division::DivideAt, _, None) => {
+                            return Err(syn::Error::new_spanned(
+                                plan.field,
+                                "dividing this field at an index requires a \
+                             #[divide_by(divide_at_fn = \"path\")] function \
+                             or a #[divide_by(method_at = \"name\")] method",
+                            ))
                         }
                     }
-                });
-                quote! {
-                    let split_fields = (#(#recurse, )*);
+                }
+            })
+        })
+        .collect::<syn::Result<Vec<_>>>()?;
+    let variables = split_variables(shape.fields);
+    Ok(quote! {
+        #(let #variables = #recurse;)*
+    })
+}
+
+/// compute base length of the structure (or of the current enum variant).
+/// Having no divided field (not counting `no_length` ones) is an error unless marked with `#[divisible(infinite)]`,
+/// on the variant or on the struct, unit structs being always infinite.
+fn generate_len_expression(plan: &Plan) -> syn::Result<TokenStream> {
+    let root = &plan.root;
+    let data = &plan.input.data;
+    let arms = plan
+        .shapes
+        .iter()
+        .map(|shape| {
+            let (path, fields) = (&shape.path, shape.fields);
+            let infinite = match *data {
+                Data::Struct(_) => plan.options.flag("infinite"),
+                _ => shape.options.flag("infinite"),
+            };
+            // only bind the divided fields giving a length
+            let mut divided = Vec::new();
+            for ((member, binding), f) in members(fields)
+                .into_iter()
+                .zip(bindings(fields))
+                .zip(&shape.plans)
+            {
+                if f.strategy.has_length() {
+                    divided.push((member, binding, &f.field.ty, &f.strategy));
                 }
             }
-            Fields::Unnamed(ref fields) => {
-                let recurse = fields.unnamed.iter().enumerate().map(|(i, f)| {
-                    let i = syn::Index::from(i);
-                    match find_strategy(&f) {
-                        DivideBy::Clone => {
-                            quote! {
-                                (self.#i.clone(), self.#i)
-                            }
-                        }
-                        DivideBy::Default => {
-                            quote! {
-                                (self.#i, Default::default())
-                            }
-                        }
-                        DivideBy::Divisible => {
-                            quote! {
-                                self.#i.divide_at(index)
+            let members: Vec<&Member> = divided.iter().map(|(member, _, _, _)| member).collect();
+            let bindings: Vec<&Ident> = divided.iter().map(|(_, binding, _, _)| binding).collect();
+            // `divide` calls `with_index` functions with half the base length
+            if bindings.is_empty() {
+                let index_function = shape
+                    .plans
+                    .iter()
+                    .find(|f| matches!(f.strategy, DivideBy::IndexFn(_)));
+                if let Some(plan) = index_function {
+                    return Err(syn::Error::new_spanned(
+                        plan.field,
+                        "dividing this field with `with_index` requires a base length: \
+                         divide another field (without no_length) or give the length with \
+                         #[length_field = \"name\"] or #[divisible(length = ...)]",
+                    ));
+                }
+            }
+            let length = match (infinite, fields, bindings.is_empty()) {
+                (Some(marker), _, false) => {
+                    return Err(syn::Error::new(
+                        marker,
+                        "#[divisible(infinite)] only applies when no field is divided",
+                    ))
+                }
+                // Unit variants have nothing left to divide
+                (None, Fields::Unit, _) if matches!(*data, Data::Enum(_)) => quote!(0),
+                // Unit structs have an infinite base length
+                (Some(_), _, _) | (None, Fields::Unit, _) => quote!(::core::primitive::usize::MAX),
+                (None, _, true) => {
+                    // point at the struct or variant name
+                    let span = path
+                        .clone()
+                        .into_iter()
+                        .last()
+                        .map_or_else(Span::call_site, |token| token.span());
+                    return Err(syn::Error::new(
+                        span,
+                        "no divided field gives a length so the base length would be infinite: \
+                         divide at least one field (without no_length), give the length with \
+                         #[length_field = \"name\"] or mark it with #[divisible(infinite)]",
+                    ));
+                }
+                (None, _, false) => {
+                    // spanned on the field type so that missing impls are reported on the field
+                    let mut lengths = divided.iter().map(|(_, b, ty, strategy)| match *strategy {
+                        DivideBy::SplitOff | DivideBy::DequeSplit | DivideBy::BtreeSplit => {
+                            quote_spanned! {ty.span()=>
+                                #b.len()
                             }
                         }
-                    }
-                });
-                quote! {
-                    let split_fields = (#(#recurse, )*);
+                        DivideBy::RangeSplit => quote_spanned! {ty.span()=>
+                            ::core::iter::ExactSizeIterator::len(#b)
+                        },
+                        DivideBy::StringChars => quote_spanned! {ty.span()=>
+                            #b.chars().count()
+                        },
+                        _ => quote_spanned! {ty.span()=>
+                            <#ty as #root::Divisible>::base_length(#b)
+                        },
+                    });
+                    // there is at least one divided field here: no need for a starting value
+                    let first = lengths.next().unwrap_or_default();
+                    lengths.fold(
+                        first,
+                        |minimum, length| quote!(::core::cmp::min(#minimum, #length)),
+                    )
                 }
-            }
-            Fields::Unit => quote!(),
-        },
-        Data::Enum(_) | Data::Union(_) => unimplemented!(),
+            };
+            Ok(quote! {
+                #path { #(#members: #bindings, )* .. } => #length,
+            })
+        })
+        .collect::<syn::Result<Vec<_>>>()?;
+    // a reference to an empty enum is not considered empty by exhaustiveness checks
+    if arms.is_empty() {
+        return Ok(quote!(match *self {}));
+    }
+    Ok(quote! {
+        match self {
+            #(#arms)*
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn suggestions() {
+        assert_eq!(suggest_strategy("cloned"), Some("clone"));
+        assert_eq!(suggest_strategy("Default"), Some("default"));
+        assert_eq!(suggest_strategy("defualt"), Some("default"));
+        assert_eq!(suggest_strategy("colne"), Some("clone"));
+        assert_eq!(suggest_strategy("arc_clon"), Some("arc_clone"));
+        assert_eq!(suggest_strategy("tkae"), Some("take"));
+        assert_eq!(suggest_strategy("split"), None);
+        assert_eq!(suggest_strategy("x"), None);
     }
 }
@@ -0,0 +1,273 @@
+// the mock traits below stand for the ones of rayon-adaptive
+extern crate self as rayon_adaptive;
+use derive_divisible::{Divisible, DivisibleAtIndex, DivisibleIntoBlocks};
+
+struct IndexedPower();
+
+trait Divisible: Sized {
+    type Power;
+    fn base_length(&self) -> usize;
+    fn divide(self) -> (Self, Self);
+}
+
+impl<T> Divisible for &[T] {
+    type Power = IndexedPower;
+    fn base_length(&self) -> usize {
+        self.len()
+    }
+    fn divide(self) -> (Self, Self) {
+        let mid = self.len() / 2;
+        self.split_at(mid)
+    }
+}
+
+trait DivisibleIntoBlocks: Divisible {
+    fn divide_at(self, index: usize) -> (Self, Self);
+}
+
+impl<T> DivisibleIntoBlocks for &[T] {
+    fn divide_at(self, index: usize) -> (Self, Self) {
+        self.split_at(index)
+    }
+}
+
+trait DivisibleAtIndex: DivisibleIntoBlocks {}
+
+impl<T> DivisibleAtIndex for &[T] {}
+
+#[derive(Divisible, DivisibleIntoBlocks, DivisibleAtIndex, Debug, PartialEq)]
+#[power(IndexedPower)]
+// tuple, struct and unit variants all together
+enum Work<'a> {
+    Slice(&'a [u32]),
+    Slices(&'a [u32], &'a [f64]),
+    Named { data: &'a [u32] },
+    Zipped { left: &'a [u32], right: &'a [u32] },
+    Done,
+}
+
+#[derive(Divisible, DivisibleIntoBlocks, Debug, PartialEq)]
+#[power(IndexedPower)]
+enum Task<'a> {
+    Running {
+        #[divide_by(clone)]
+        state: String,
+        #[divide_by(default)]
+        processed: usize,
+        items: &'a [usize],
+    },
+    Waiting(#[divide_by(copy)] u64, &'a [usize]),
+    Indexed {
+        #[divide_by(copy)]
+        index: usize,
+        items: &'a [usize],
+    },
+    Idle,
+    #[divisible(infinite)]
+    Forever,
+}
+
+#[derive(Divisible, DivisibleIntoBlocks, Debug, PartialEq)]
+#[power(IndexedPower)]
+enum Input<'a> {
+    Data(&'a [u32]),
+    #[divisible(empty)]
+    Empty,
+}
+
+#[derive(Divisible, DivisibleAtIndex, Debug, PartialEq)]
+#[power(IndexedPower)]
+#[divisible(with_blocks)]
+enum Indexed<'a> {
+    Data(&'a [u32]),
+    Nothing,
+}
+
+fn divide_at<D: DivisibleAtIndex>(d: D, index: usize) -> (D, D) {
+    d.divide_at(index)
+}
+
+#[test]
+fn tuple_variant() {
+    let v = vec![1, 2, 3, 4];
+    let work = Work::Slice(&v);
+    assert_eq!(work.base_length(), 4);
+    assert_eq!(work.divide(), (Work::Slice(&v[..2]), Work::Slice(&v[2..])));
+}
+
+#[test]
+fn tuple_variant_with_several_fields() {
+    let v = vec![1, 2, 3, 4];
+    let w = vec![1.5, 2.5, 3.5];
+    let work = Work::Slices(&v, &w);
+    assert_eq!(work.base_length(), 3);
+    assert_eq!(
+        work.divide(),
+        (
+            Work::Slices(&v[..2], &w[..1]),
+            Work::Slices(&v[2..], &w[1..])
+        )
+    );
+}
+
+#[test]
+fn struct_variant() {
+    let v = vec![1, 2, 3, 4];
+    let work = Work::Named { data: &v };
+    assert_eq!(work.base_length(), 4);
+    assert_eq!(
+        work.divide(),
+        (Work::Named { data: &v[..2] }, Work::Named { data: &v[2..] })
+    );
+}
+
+#[test]
+fn struct_variant_with_several_fields() {
+    let v = vec![1, 2, 3, 4];
+    let work = Work::Zipped {
+        left: &v,
+        right: &v[1..],
+    };
+    assert_eq!(work.base_length(), 3);
+    let (left, right) = work.divide();
+    assert_eq!(
+        left,
+        Work::Zipped {
+            left: &v[..2],
+            right: &v[1..2]
+        }
+    );
+    assert_eq!(
+        right,
+        Work::Zipped {
+            left: &v[2..],
+            right: &v[2..]
+        }
+    );
+}
+
+#[test]
+fn divide_at_index() {
+    let v = vec![1, 2, 3, 4];
+    let w = vec![1.5, 2.5, 3.5];
+    assert_eq!(
+        divide_at(Work::Slices(&v, &w), 1),
+        (
+            Work::Slices(&v[..1], &w[..1]),
+            Work::Slices(&v[1..], &w[1..])
+        )
+    );
+    assert_eq!(
+        divide_at(Work::Named { data: &v }, 3),
+        (Work::Named { data: &v[..3] }, Work::Named { data: &v[3..] })
+    );
+}
+
+#[test]
+fn unit_variant() {
+    assert_eq!(Work::Done.base_length(), 0);
+    assert_eq!(Work::Done.divide(), (Work::Done, Work::Done));
+    assert_eq!(divide_at(Work::Done, 0), (Work::Done, Work::Done));
+}
+
+#[test]
+fn variant_strategies() {
+    let items = vec![5, 6, 7];
+    let task = Task::Running {
+        state: "running".to_owned(),
+        processed: 2,
+        items: &items,
+    };
+    assert_eq!(task.base_length(), 3);
+    assert_eq!(
+        task.divide(),
+        (
+            Task::Running {
+                state: "running".to_owned(),
+                processed: 2,
+                items: &items[..1],
+            },
+            Task::Running {
+                state: "running".to_owned(),
+                processed: 0,
+                items: &items[1..],
+            }
+        )
+    );
+    assert_eq!(
+        Task::Waiting(7, &items).divide(),
+        (Task::Waiting(7, &items[..1]), Task::Waiting(7, &items[1..]))
+    );
+}
+
+#[test]
+fn variant_strategies_at_index() {
+    let items = vec![5, 6, 7];
+    for &i in &[0, 1, 3] {
+        assert_eq!(
+            Task::Waiting(7, &items).divide_at(i),
+            (Task::Waiting(7, &items[..i]), Task::Waiting(7, &items[i..]))
+        );
+        assert_eq!(
+            Task::Indexed {
+                index: 2,
+                items: &items
+            }
+            .divide_at(i),
+            (
+                Task::Indexed {
+                    index: 2,
+                    items: &items[..i]
+                },
+                Task::Indexed {
+                    index: 2,
+                    items: &items[i..]
+                }
+            )
+        );
+        assert_eq!(Task::Idle.divide_at(i), (Task::Idle, Task::Idle));
+    }
+}
+
+#[test]
+fn empty_variant_of_strategies() {
+    assert_eq!(Task::Idle.base_length(), 0);
+    assert_eq!(Task::Idle.divide(), (Task::Idle, Task::Idle));
+}
+
+#[test]
+fn infinite_variant() {
+    assert_eq!(Task::Forever.base_length(), usize::MAX);
+    assert_eq!(Task::Forever.divide(), (Task::Forever, Task::Forever));
+}
+
+#[test]
+fn empty_variant() {
+    let v = vec![1, 2, 3, 4];
+    assert_eq!(
+        Input::Data(&v).divide_at(0),
+        (Input::Data(&v[..0]), Input::Data(&v))
+    );
+    assert_eq!(
+        Input::Data(&v).divide_at(v.len()),
+        (Input::Data(&v), Input::Empty)
+    );
+    assert_eq!(
+        Input::Data(&v[..0]).divide(),
+        (Input::Data(&v[..0]), Input::Empty)
+    );
+    assert_eq!(Input::Empty.divide(), (Input::Empty, Input::Empty));
+}
+
+#[test]
+fn with_blocks() {
+    let v = vec![1, 2, 3, 4];
+    assert_eq!(
+        divide_at(Indexed::Data(&v), 1),
+        (Indexed::Data(&v[..1]), Indexed::Data(&v[1..]))
+    );
+    assert_eq!(
+        divide_at(Indexed::Nothing, 0),
+        (Indexed::Nothing, Indexed::Nothing)
+    );
+}
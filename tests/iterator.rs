@@ -0,0 +1,144 @@
+// the mock traits below stand for the ones of rayon-adaptive
+extern crate self as rayon_adaptive;
+use derive_divisible::{Divisible, Edible, ParallelIterator};
+use std::iter::Map;
+use std::ops::Range;
+
+struct IndexedPower();
+
+trait Divisible: Sized {
+    type Power;
+    fn base_length(&self) -> usize;
+    fn divide(self) -> (Self, Self);
+}
+
+trait ParallelIterator: Divisible {
+    type Item;
+    type SequentialIterator: Iterator<Item = Self::Item>;
+    fn iter(self, size: usize) -> (Self::SequentialIterator, Self);
+}
+
+trait Edible: Sized {
+    type Item;
+    type SequentialIterator: Iterator<Item = Self::Item>;
+    fn iter(self, size: usize) -> (Self::SequentialIterator, Self);
+}
+
+#[derive(Debug, PartialEq)]
+struct Indices(Range<usize>);
+
+impl Divisible for Indices {
+    type Power = IndexedPower;
+    fn base_length(&self) -> usize {
+        self.0.len()
+    }
+    fn divide(self) -> (Self, Self) {
+        let middle = self.0.start + self.0.len() / 2;
+        (Indices(self.0.start..middle), Indices(middle..self.0.end))
+    }
+}
+
+impl ParallelIterator for Indices {
+    type Item = usize;
+    type SequentialIterator = Range<usize>;
+    fn iter(self, size: usize) -> (Self::SequentialIterator, Self) {
+        let middle = self.0.start + size;
+        (self.0.start..middle, Indices(middle..self.0.end))
+    }
+}
+
+// item and sequential iterator are the ones of the inner iterator
+#[derive(Divisible, ParallelIterator, Debug, PartialEq)]
+#[power(IndexedPower)]
+struct Wrapper(#[divisible(inner)] Indices);
+
+#[derive(Divisible, ParallelIterator)]
+#[power(IndexedPower)]
+#[item(u64)]
+#[sequential_iterator(Map<Range<usize>, fn(usize) -> u64>)]
+#[iterator_extraction(iter.map(self.op))]
+struct Mapped {
+    #[divisible(inner)]
+    indices: Indices,
+    #[divide_by(copy)]
+    op: fn(usize) -> u64,
+}
+
+#[derive(Debug, PartialEq)]
+struct Pile(Vec<u32>);
+
+impl Edible for Pile {
+    type Item = u32;
+    type SequentialIterator = std::vec::IntoIter<u32>;
+    fn iter(mut self, size: usize) -> (Self::SequentialIterator, Self) {
+        let rest = self.0.split_off(size);
+        (self.0.into_iter(), Pile(rest))
+    }
+}
+
+// generic inner iterators get an `Edible` bound
+#[derive(Edible, Debug, PartialEq)]
+struct Labeled<E> {
+    #[divisible(inner)]
+    pile: E,
+    label: &'static str,
+}
+
+#[derive(Edible)]
+#[sequential_iterator(Map<std::vec::IntoIter<u32>, fn(u32) -> u32>)]
+#[iterator_extraction(iter.map(double as fn(u32) -> u32))]
+struct Doubled(#[divisible(inner)] Pile);
+
+fn double(x: u32) -> u32 {
+    2 * x
+}
+
+fn square(x: usize) -> u64 {
+    (x * x) as u64
+}
+
+#[test]
+fn inner_iterator() {
+    let wrapper = Wrapper(Indices(0..10));
+    assert_eq!(wrapper.base_length(), 10);
+    let (sequential, remaining) = wrapper.iter(3);
+    assert_eq!(sequential.collect::<Vec<_>>(), vec![0, 1, 2]);
+    assert_eq!(remaining, Wrapper(Indices(3..10)));
+}
+
+#[test]
+fn iterator_extraction() {
+    let mapped = Mapped {
+        indices: Indices(2..6),
+        op: square,
+    };
+    let (left, right) = mapped.divide();
+    let (sequential, remaining) = right.iter(1);
+    assert_eq!(sequential.collect::<Vec<_>>(), vec![16]);
+    assert_eq!(remaining.indices, Indices(5..6));
+    assert_eq!(left.iter(2).0.sum::<u64>(), 4 + 9);
+}
+
+#[test]
+fn generic_inner_iterator() {
+    let labeled = Labeled {
+        pile: Pile(vec![1, 2, 3]),
+        label: "pile",
+    };
+    let (sequential, remaining) = Edible::iter(labeled, 2);
+    assert_eq!(sequential.collect::<Vec<_>>(), vec![1, 2]);
+    assert_eq!(
+        remaining,
+        Labeled {
+            pile: Pile(vec![3]),
+            label: "pile"
+        }
+    );
+}
+
+#[test]
+fn edible_iterator_extraction() {
+    let (sequential, remaining) = Edible::iter(Doubled(Pile(vec![1, 2, 3])), 2);
+    assert_eq!(sequential.collect::<Vec<_>>(), vec![2, 4]);
+    assert_eq!(remaining.0, Pile(vec![3]));
+}
@@ -0,0 +1,63 @@
+// the mock trait below stands for a merging trait of rayon-adaptive
+extern crate self as rayon_adaptive;
+use derive_divisible::Merge;
+
+trait Merge {
+    fn merge(left: Self, right: Self) -> Self;
+}
+
+impl Merge for usize {
+    fn merge(left: Self, right: Self) -> Self {
+        left.max(right)
+    }
+}
+
+#[derive(Merge, Debug, PartialEq)]
+struct Statistics {
+    #[merge_by(extend)]
+    values: Vec<u32>,
+    #[merge_by(add)]
+    sum: u32,
+    longest: usize,
+    #[merge_by(left)]
+    first: Option<u32>,
+    #[merge_by(right)]
+    last: Option<u32>,
+}
+
+#[derive(Merge, Debug, PartialEq)]
+struct Collected<T, U>(#[merge_by(extend)] Vec<T>, U);
+
+#[test]
+fn merge_strategies() {
+    let left = Statistics {
+        values: vec![1, 2],
+        sum: 3,
+        longest: 2,
+        first: Some(1),
+        last: Some(2),
+    };
+    let right = Statistics {
+        values: vec![3],
+        sum: 3,
+        longest: 1,
+        first: Some(3),
+        last: Some(3),
+    };
+    assert_eq!(
+        Merge::merge(left, right),
+        Statistics {
+            values: vec![1, 2, 3],
+            sum: 6,
+            longest: 2,
+            first: Some(1),
+            last: Some(3),
+        }
+    );
+}
+
+#[test]
+fn generic_tuple_fields() {
+    let merged = Merge::merge(Collected(vec!['a'], 1usize), Collected(vec!['b'], 4));
+    assert_eq!(merged, Collected(vec!['a', 'b'], 4));
+}
@@ -0,0 +1,208 @@
+// every attribute of the derives, in all the forms they accept
+mod traits {
+    pub struct IndexedPower;
+
+    pub trait Divisible: Sized {
+        type Power;
+        fn base_length(&self) -> usize;
+        fn divide(self) -> (Self, Self);
+    }
+
+    pub trait DivisibleIntoBlocks: Divisible {
+        fn divide_at(self, index: usize) -> (Self, Self);
+    }
+
+    pub trait DivisibleAtIndex: DivisibleIntoBlocks {}
+
+    pub trait Merge {
+        fn merge(left: Self, right: Self) -> Self;
+    }
+
+    impl<T> Divisible for Vec<T> {
+        type Power = IndexedPower;
+        fn base_length(&self) -> usize {
+            self.len()
+        }
+        fn divide(self) -> (Self, Self) {
+            let mid = self.len() / 2;
+            self.divide_at(mid)
+        }
+    }
+
+    impl<T> DivisibleIntoBlocks for Vec<T> {
+        fn divide_at(mut self, index: usize) -> (Self, Self) {
+            let right = self.split_off(index);
+            (self, right)
+        }
+    }
+
+    impl<T> DivisibleAtIndex for Vec<T> {}
+
+    impl Merge for usize {
+        fn merge(left: Self, right: Self) -> Self {
+            left.max(right)
+        }
+    }
+}
+
+use derive_divisible::{Divisible, DivisibleAtIndex, DivisibleIntoBlocks, Merge};
+use traits::{Divisible as _, Merge as _};
+
+fn swap(pair: (u8, u8)) -> ((u8, u8), (u8, u8)) {
+    ((pair.0, pair.1), (pair.1, pair.0))
+}
+
+fn swap_at(pair: (u8, u8), _index: usize) -> ((u8, u8), (u8, u8)) {
+    swap(pair)
+}
+
+#[derive(Divisible, DivisibleIntoBlocks, DivisibleAtIndex, Debug, PartialEq)]
+#[power(traits::IndexedPower)]
+#[divisible(crate = "traits")]
+#[divide_by(all = "clone")]
+#[length_field = "items"]
+struct Everything {
+    #[divide_by(divisible)]
+    items: Vec<u32>,
+    #[divide_by(divisible, no_length)]
+    buffer: Vec<u32>,
+    // arguments can also be given by several attributes
+    #[divide_by(divisible)]
+    #[divide_by(no_length)]
+    spare: Vec<u32>,
+    #[divide_by(ratio = 1/2)]
+    weights: Vec<u32>,
+    #[divide_by(half, no_length)]
+    scores: Vec<u32>,
+    #[divide_by(ratio = 1/4, no_length)]
+    ranks: Vec<u32>,
+    #[divide_by("default")]
+    processed: usize,
+    #[divide_by(fn = "swap", divide_at_fn = "swap_at")]
+    pair: (u8, u8),
+    #[divide_by(fn = "swap")]
+    #[divide_by(divide_at_fn = "swap_at")]
+    other_pair: (u8, u8),
+    label: String,
+}
+
+#[derive(Divisible, Debug, PartialEq)]
+#[power(traits::IndexedPower)]
+#[divisible(crate = "traits", length = self.end - self.start, no_bounds)]
+struct Span {
+    #[divide_by(copy)]
+    start: usize,
+    #[divide_by(copy)]
+    end: usize,
+}
+
+// fields cut at a ratio can be left out of the length as well
+#[derive(Divisible, Debug, PartialEq)]
+#[power(traits::IndexedPower)]
+#[divisible(crate = "traits")]
+struct Weighted {
+    items: Vec<u32>,
+    #[divide_by(ratio = 1/4, no_length)]
+    weights: Vec<u32>,
+}
+
+// options can also be given by several attributes
+#[derive(DivisibleIntoBlocks, Debug, PartialEq)]
+#[power(traits::IndexedPower)]
+#[divisible(crate = "traits")]
+#[divisible(generate_try_divide)]
+#[divisible(with_length)]
+struct Blocks {
+    items: Vec<u32>,
+}
+
+#[derive(Merge, Debug, PartialEq)]
+#[divisible(crate = "traits")]
+struct Merged {
+    #[merge_by(extend)]
+    values: Vec<u32>,
+    #[merge_by(add)]
+    sum: u32,
+    longest: usize,
+    #[merge_by(left)]
+    first: u8,
+    #[merge_by(right)]
+    last: u8,
+}
+
+fn main() {
+    let everything = Everything {
+        items: vec![1, 2, 3, 4],
+        buffer: vec![5, 6],
+        spare: vec![],
+        weights: vec![7, 8, 9, 10],
+        scores: vec![1, 2],
+        ranks: vec![1, 2, 3, 4],
+        processed: 3,
+        pair: (1, 2),
+        other_pair: (3, 4),
+        label: "all".to_owned(),
+    };
+    assert_eq!(everything.base_length(), 4);
+    let (left, right) = everything.divide();
+    assert_eq!(
+        (left.items, left.buffer, left.weights, left.processed, left.pair),
+        (vec![1, 2], vec![5], vec![7, 8], 3, (1, 2))
+    );
+    assert_eq!(
+        (right.items, right.buffer, right.processed, right.pair, right.label),
+        (vec![3, 4], vec![6], 0, (2, 1), "all".to_owned())
+    );
+    assert_eq!(
+        (left.spare, left.other_pair, right.spare, right.other_pair),
+        (vec![], (3, 4), vec![], (4, 3))
+    );
+
+    let span = Span { start: 2, end: 7 };
+    assert_eq!(span.base_length(), 5);
+
+    let weighted = Weighted {
+        items: vec![1, 2],
+        weights: vec![1, 2, 3, 4, 5, 6, 7, 8],
+    };
+    assert_eq!(weighted.base_length(), 2);
+    let (left, right) = weighted.divide();
+    assert_eq!((left.items, left.weights), (vec![1], vec![1, 2]));
+    assert_eq!((right.items, right.weights), (vec![2], vec![3, 4, 5, 6, 7, 8]));
+
+    let blocks = Blocks {
+        items: vec![1, 2, 3],
+    };
+    assert_eq!(
+        blocks.try_divide(),
+        Some((Blocks { items: vec![1] }, Blocks { items: vec![2, 3] }))
+    );
+    assert_eq!(Blocks { items: vec![] }.try_divide(), None);
+
+    let merged = Merged::merge(
+        Merged {
+            values: vec![1],
+            sum: 1,
+            longest: 4,
+            first: 1,
+            last: 1,
+        },
+        Merged {
+            values: vec![2],
+            sum: 2,
+            longest: 3,
+            first: 2,
+            last: 2,
+        },
+    );
+    assert_eq!(
+        merged,
+        Merged {
+            values: vec![1, 2],
+            sum: 3,
+            longest: 4,
+            first: 1,
+            last: 2,
+        }
+    );
+}
@@ -0,0 +1,74 @@
+#![allow(unexpected_cfgs)]
+extern crate self as rayon_adaptive;
+use derive_divisible::{Divisible, DivisibleIntoBlocks, Merge};
+
+struct IndexedPower;
+
+trait Divisible: Sized {
+    type Power;
+    fn base_length(&self) -> usize;
+    fn divide(self) -> (Self, Self);
+}
+
+trait DivisibleIntoBlocks: Divisible {
+    fn divide_at(self, index: usize) -> (Self, Self);
+}
+
+trait Merge {
+    fn merge(left: Self, right: Self) -> Self;
+}
+
+impl<T> Divisible for &[T] {
+    type Power = IndexedPower;
+    fn base_length(&self) -> usize {
+        self.len()
+    }
+    fn divide(self) -> (Self, Self) {
+        self.split_at(self.len() / 2)
+    }
+}
+
+impl<T> DivisibleIntoBlocks for &[T] {
+    fn divide_at(self, index: usize) -> (Self, Self) {
+        self.split_at(index)
+    }
+}
+
+// `Logger` does not exist: fields of disabled features must not be referenced.
+// They are removed before derives run so the generated code never sees them.
+#[derive(Divisible, DivisibleIntoBlocks, Debug, PartialEq)]
+#[power(IndexedPower)]
+enum Work<'a> {
+    Logged {
+        #[cfg(feature = "logging")]
+        #[divide_by(clone)]
+        log: Logger,
+        #[cfg(feature = "logging")]
+        logged: &'a [Logger],
+        items: &'a [u32],
+    },
+    Other(#[cfg(feature = "logging")] Logger, &'a [u32]),
+}
+
+#[derive(Merge, Debug, PartialEq)]
+struct Counters {
+    #[cfg(feature = "logging")]
+    log: Logger,
+    #[merge_by(add)]
+    count: u32,
+}
+
+fn main() {
+    let items = [1, 2, 3];
+    let work = Work::Logged { items: &items };
+    assert_eq!(work.base_length(), 3);
+    assert_eq!(
+        work.divide_at(1),
+        (
+            Work::Logged { items: &items[..1] },
+            Work::Logged { items: &items[1..] }
+        )
+    );
+    let counters = Counters::merge(Counters { count: 1 }, Counters { count: 2 });
+    assert_eq!(counters, Counters { count: 3 });
+}
@@ -0,0 +1,70 @@
+extern crate self as rayon_adaptive;
+use derive_divisible::{Divisible, DivisibleIntoBlocks};
+
+struct IndexedPower;
+
+trait Divisible: Sized {
+    type Power;
+    fn base_length(&self) -> usize;
+    fn divide(self) -> (Self, Self);
+}
+
+trait DivisibleIntoBlocks: Divisible {
+    fn divide_at(self, index: usize) -> (Self, Self);
+}
+
+impl<T> Divisible for &[T] {
+    type Power = IndexedPower;
+    fn base_length(&self) -> usize {
+        self.len()
+    }
+    fn divide(self) -> (Self, Self) {
+        self.split_at(self.len() / 2)
+    }
+}
+
+impl<T> DivisibleIntoBlocks for &[T] {
+    fn divide_at(self, index: usize) -> (Self, Self) {
+        self.split_at(index)
+    }
+}
+
+#[derive(Divisible, DivisibleIntoBlocks, Debug, PartialEq)]
+#[power(IndexedPower)]
+struct Chunks<'a, const N: usize> {
+    #[divide_by(copy)]
+    sizes: [usize; N],
+    data: &'a [u8],
+}
+
+#[derive(Divisible, DivisibleIntoBlocks, Debug, PartialEq)]
+#[power(IndexedPower)]
+struct Windows<'a, T: Copy, const N: usize>(#[divide_by(clone)] [T; N], &'a [T]);
+
+fn main() {
+    let data = [1, 2, 3, 4];
+    let chunks = Chunks {
+        sizes: [2, 2],
+        data: &data,
+    };
+    assert_eq!(chunks.base_length(), 4);
+    assert_eq!(
+        chunks.divide_at(1),
+        (
+            Chunks {
+                sizes: [2, 2],
+                data: &data[..1],
+            },
+            Chunks {
+                sizes: [2, 2],
+                data: &data[1..],
+            }
+        )
+    );
+
+    let windows = Windows([0u8; 3], &data);
+    assert_eq!(
+        windows.divide(),
+        (Windows([0; 3], &data[..2]), Windows([0; 3], &data[2..]))
+    );
+}
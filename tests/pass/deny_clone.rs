@@ -0,0 +1,53 @@
+extern crate self as rayon_adaptive;
+use derive_divisible::Divisible;
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+struct IndexedPower;
+
+trait Divisible: Sized {
+    type Power;
+    fn base_length(&self) -> usize;
+    fn divide(self) -> (Self, Self);
+}
+
+impl<T> Divisible for &[T] {
+    type Power = IndexedPower;
+    fn base_length(&self) -> usize {
+        self.len()
+    }
+    fn divide(self) -> (Self, Self) {
+        self.split_at(self.len() / 2)
+    }
+}
+
+// no field is cloned (markers are free to clone)
+#[derive(Divisible)]
+#[power(IndexedPower)]
+#[divisible(deny_clone)]
+struct Data<'a, T> {
+    #[divide_by(share)]
+    name: Arc<String>,
+    #[divide_by(copy)]
+    id: u64,
+    #[divide_by(default)]
+    processed: usize,
+    items: &'a [u32],
+    marker: PhantomData<T>,
+}
+
+fn main() {
+    let items = [1, 2, 3, 4];
+    let data: Data<u8> = Data {
+        name: Arc::new("data".to_owned()),
+        id: 3,
+        processed: 2,
+        items: &items,
+        marker: PhantomData,
+    };
+    let (left, right) = data.divide();
+    assert_eq!((left.items, right.items), (&items[..2], &items[2..]));
+    assert_eq!((left.processed, right.processed), (2, 0));
+    assert!(Arc::ptr_eq(&left.name, &right.name));
+    assert_eq!(left.id + right.id, 6);
+}
@@ -0,0 +1,29 @@
+extern crate self as rayon_adaptive;
+use derive_divisible::FullyDivisible;
+
+struct IndexedPower;
+
+trait Divisible: Sized {
+    type Power;
+    fn base_length(&self) -> usize;
+    fn divide(self) -> (Self, Self);
+}
+
+trait DivisibleIntoBlocks: Divisible {
+    fn divide_at(self, index: usize) -> (Self, Self);
+}
+
+trait DivisibleAtIndex: DivisibleIntoBlocks {}
+
+// no value to divide, but still usable where divisible types are expected
+#[derive(FullyDivisible)]
+#[power(IndexedPower)]
+enum Never {}
+
+fn length<D: DivisibleAtIndex>(divisible: &D) -> usize {
+    divisible.base_length()
+}
+
+fn main() {
+    let _ = length::<Never>;
+}
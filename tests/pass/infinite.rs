@@ -0,0 +1,51 @@
+extern crate self as rayon_adaptive;
+use derive_divisible::Divisible;
+
+struct IndexedPower;
+
+trait Divisible: Sized {
+    type Power;
+    fn base_length(&self) -> usize;
+    fn divide(self) -> (Self, Self);
+}
+
+#[derive(Divisible, Debug, PartialEq)]
+#[power(IndexedPower)]
+#[divisible(infinite)]
+struct Settings {
+    #[divide_by(clone)]
+    name: String,
+    #[divide_by(default)]
+    level: u32,
+}
+
+#[derive(Divisible, Debug, PartialEq)]
+#[power(IndexedPower)]
+enum Job {
+    #[divisible(infinite)]
+    Named(#[divide_by(clone)] String),
+    Idle,
+}
+
+fn main() {
+    let settings = Settings {
+        name: "fast".to_owned(),
+        level: 3,
+    };
+    assert_eq!(settings.base_length(), usize::MAX);
+    assert_eq!(
+        settings.divide(),
+        (
+            Settings {
+                name: "fast".to_owned(),
+                level: 3
+            },
+            Settings {
+                name: "fast".to_owned(),
+                level: 0
+            }
+        )
+    );
+    assert_eq!(Job::Named("job".to_owned()).base_length(), usize::MAX);
+    assert_eq!(Job::Idle.base_length(), 0);
+}
@@ -0,0 +1,61 @@
+extern crate self as rayon_adaptive;
+use derive_divisible::{Divisible, DivisibleIntoBlocks};
+
+struct IndexedPower;
+
+trait Divisible: Sized {
+    type Power;
+    fn base_length(&self) -> usize;
+    fn divide(self) -> (Self, Self);
+}
+
+trait DivisibleIntoBlocks: Divisible {
+    fn divide_at(self, index: usize) -> (Self, Self);
+}
+
+impl<T> Divisible for &[T] {
+    type Power = IndexedPower;
+    fn base_length(&self) -> usize {
+        self.len()
+    }
+    fn divide(self) -> (Self, Self) {
+        self.split_at(self.len() / 2)
+    }
+}
+
+impl<T> DivisibleIntoBlocks for &[T] {
+    fn divide_at(self, index: usize) -> (Self, Self) {
+        self.split_at(index)
+    }
+}
+
+#[derive(DivisibleIntoBlocks, Debug, PartialEq)]
+#[power(IndexedPower)]
+#[divisible(with_length)]
+struct Foo<'a>(&'a [u8]);
+
+#[derive(Divisible, DivisibleIntoBlocks, Debug, PartialEq)]
+#[power(IndexedPower)]
+struct SliceWrapper<'a, T> {
+    data: &'a [T],
+}
+
+// both sides keep borrowing the original data
+fn halves<'a>(foo: Foo<'a>, index: usize) -> (Foo<'a>, Foo<'a>) {
+    foo.divide_at(index)
+}
+
+fn main() {
+    let bytes = [1, 2, 3, 4, 5];
+    let (left, right) = halves(Foo(&bytes), 2);
+    assert_eq!((left, right), (Foo(&bytes[..2]), Foo(&bytes[2..])));
+    assert_eq!(Foo(&bytes).base_length(), 5);
+    assert_eq!(Foo(&bytes).divide(), (Foo(&bytes[..2]), Foo(&bytes[2..])));
+
+    let words = vec!["a".to_owned(), "b".to_owned(), "c".to_owned()];
+    let wrapper = SliceWrapper { data: &words[..] };
+    let (left, right) = wrapper.divide();
+    assert_eq!((left.data, right.data), (&words[..1], &words[1..]));
+    let (left, right) = right.divide_at(1);
+    assert_eq!((left.data, right.data), (&words[1..2], &words[2..]));
+}
@@ -0,0 +1,101 @@
+extern crate self as rayon_adaptive;
+use derive_divisible::{Divisible, DivisibleAtIndex, DivisibleIntoBlocks};
+
+struct IndexedPower;
+
+trait Divisible: Sized {
+    type Power;
+    fn base_length(&self) -> usize;
+    fn divide(self) -> (Self, Self);
+}
+
+trait DivisibleIntoBlocks: Divisible {
+    fn divide_at(self, index: usize) -> (Self, Self);
+}
+
+trait DivisibleAtIndex: DivisibleIntoBlocks {}
+
+impl<T> Divisible for Vec<T> {
+    type Power = IndexedPower;
+    fn base_length(&self) -> usize {
+        self.len()
+    }
+    fn divide(self) -> (Self, Self) {
+        let mid = self.len() / 2;
+        self.divide_at(mid)
+    }
+}
+
+impl<T> DivisibleIntoBlocks for Vec<T> {
+    fn divide_at(mut self, index: usize) -> (Self, Self) {
+        let right = self.split_off(index);
+        (self, right)
+    }
+}
+
+impl<T> DivisibleAtIndex for Vec<T> {}
+
+#[derive(Divisible, DivisibleIntoBlocks, DivisibleAtIndex, Debug, PartialEq)]
+#[power(IndexedPower)]
+struct Named {
+    items: Vec<u32>,
+    #[divide_by(clone)]
+    label: String,
+}
+
+// bounds on the type parameters are inferred from the fields
+#[derive(Divisible, DivisibleIntoBlocks, DivisibleAtIndex, Debug, PartialEq)]
+#[power(IndexedPower)]
+struct Generic<T, D> {
+    items: Vec<T>,
+    data: D,
+    #[divide_by(default)]
+    processed: T,
+}
+
+fn divide_at_index<D: DivisibleAtIndex>(d: D, index: usize) -> (D, D) {
+    d.divide_at(index)
+}
+
+fn main() {
+    let named = Named {
+        items: vec![1, 2, 3],
+        label: "named".to_owned(),
+    };
+    assert_eq!(named.base_length(), 3);
+    assert_eq!(
+        divide_at_index(named, 2),
+        (
+            Named {
+                items: vec![1, 2],
+                label: "named".to_owned(),
+            },
+            Named {
+                items: vec![3],
+                label: "named".to_owned(),
+            }
+        )
+    );
+
+    let generic = Generic {
+        items: vec![1u8, 2, 3, 4],
+        data: vec!['a', 'b'],
+        processed: 5,
+    };
+    assert_eq!(generic.base_length(), 2);
+    assert_eq!(
+        generic.divide(),
+        (
+            Generic {
+                items: vec![1, 2],
+                data: vec!['a'],
+                processed: 5,
+            },
+            Generic {
+                items: vec![3, 4],
+                data: vec!['b'],
+                processed: 0,
+            }
+        )
+    );
+}
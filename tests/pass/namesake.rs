@@ -0,0 +1,40 @@
+// wrappers named after the type they wrap are not recursive
+extern crate self as rayon_adaptive;
+use derive_divisible::Divisible;
+
+struct IndexedPower;
+
+trait Divisible: Sized {
+    type Power;
+    fn base_length(&self) -> usize;
+    fn divide(self) -> (Self, Self);
+}
+
+mod inner {
+    pub struct Iter(pub std::ops::Range<usize>);
+
+    impl crate::Divisible for Iter {
+        type Power = crate::IndexedPower;
+        fn base_length(&self) -> usize {
+            self.0.len()
+        }
+        fn divide(self) -> (Self, Self) {
+            let middle = self.0.start + self.0.len() / 2;
+            (Iter(self.0.start..middle), Iter(middle..self.0.end))
+        }
+    }
+}
+
+#[derive(Divisible)]
+#[power(IndexedPower)]
+struct Iter {
+    inner: inner::Iter,
+}
+
+fn main() {
+    let (left, right) = Iter {
+        inner: inner::Iter(0..4),
+    }
+    .divide();
+    assert_eq!((left.inner.0, right.inner.0), (0..2, 2..4));
+}
@@ -0,0 +1,49 @@
+extern crate self as rayon_adaptive;
+use derive_divisible::Divisible;
+
+mod powers {
+    pub struct IndexedPower;
+}
+
+trait Divisible: Sized {
+    type Power;
+    fn base_length(&self) -> usize;
+    fn divide(self) -> (Self, Self);
+}
+
+impl<T> Divisible for &[T] {
+    type Power = powers::IndexedPower;
+    fn base_length(&self) -> usize {
+        self.len()
+    }
+    fn divide(self) -> (Self, Self) {
+        self.split_at(self.len() / 2)
+    }
+}
+
+// a fully qualified power next to a type parameter
+#[derive(Divisible)]
+#[power(crate::powers::IndexedPower)]
+struct Qualified<'a, P> {
+    items: &'a [P],
+}
+
+// the power given by a type parameter
+#[derive(Divisible)]
+#[power(Pow)]
+struct Parametrized<'a, Pow> {
+    items: &'a [u32],
+    #[divide_by(ignore)]
+    power: std::marker::PhantomData<Pow>,
+}
+
+fn main() {
+    let items = [1, 2, 3];
+    assert_eq!(Qualified { items: &items }.base_length(), 3);
+    let parametrized: Parametrized<powers::IndexedPower> = Parametrized {
+        items: &items,
+        power: std::marker::PhantomData,
+    };
+    let (left, right) = parametrized.divide();
+    assert_eq!((left.items, right.items), (&items[..1], &items[1..]));
+}
@@ -0,0 +1,90 @@
+// fields named like the functions and constants of their own divide_by attributes
+extern crate self as rayon_adaptive;
+use derive_divisible::{Divisible, DivisibleIntoBlocks};
+
+struct IndexedPower;
+
+trait Divisible: Sized {
+    type Power;
+    fn base_length(&self) -> usize;
+    fn divide(self) -> (Self, Self);
+}
+
+trait DivisibleIntoBlocks: Divisible {
+    fn divide_at(self, index: usize) -> (Self, Self);
+}
+
+impl<T> Divisible for &[T] {
+    type Power = IndexedPower;
+    fn base_length(&self) -> usize {
+        self.len()
+    }
+    fn divide(self) -> (Self, Self) {
+        self.split_at(self.len() / 2)
+    }
+}
+
+impl<T> DivisibleIntoBlocks for &[T] {
+    fn divide_at(self, index: usize) -> (Self, Self) {
+        self.split_at(index)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct Counter(u32);
+
+fn split(counter: Counter) -> (Counter, Counter) {
+    (Counter(counter.0 / 2), Counter(counter.0 - counter.0 / 2))
+}
+
+fn split_at(counter: Counter, index: usize) -> (Counter, Counter) {
+    let left = counter.0.min(index as u32);
+    (Counter(left), Counter(counter.0 - left))
+}
+
+fn output(output: &Counter) -> Counter {
+    Counter(output.0)
+}
+
+#[allow(non_upper_case_globals)]
+const limit: u32 = 10;
+
+#[derive(Divisible, DivisibleIntoBlocks)]
+#[power(IndexedPower)]
+struct Shadowing<'a> {
+    #[divide_by(fn = "split", divide_at_fn = "split_at")]
+    split: Counter,
+    #[divide_by(with_index = "split_at")]
+    split_at: Counter,
+    #[divide_by(dup_with = "output")]
+    output: Counter,
+    #[divide_by(default = "limit")]
+    limit: u32,
+    data: &'a [u32],
+}
+
+fn main() {
+    let shadowing = Shadowing {
+        split: Counter(5),
+        split_at: Counter(5),
+        output: Counter(1),
+        limit: 3,
+        data: &[1, 2, 3, 4],
+    };
+    let (left, right) = shadowing.divide();
+    assert_eq!((left.split, right.split), (Counter(2), Counter(3)));
+    assert_eq!((left.split_at, right.split_at), (Counter(2), Counter(3)));
+    assert_eq!((left.output, right.output), (Counter(1), Counter(1)));
+    assert_eq!((left.limit, right.limit), (3, limit));
+    let shadowing = Shadowing {
+        split: Counter(5),
+        split_at: Counter(5),
+        output: Counter(1),
+        limit: 3,
+        data: &[1, 2, 3, 4],
+    };
+    let (left, right) = shadowing.divide_at(1);
+    assert_eq!((left.split, right.split), (Counter(1), Counter(4)));
+    assert_eq!((left.split_at, right.split_at), (Counter(1), Counter(4)));
+    assert_eq!((left.data, right.data), (&[1][..], &[2, 3, 4][..]));
+}
@@ -0,0 +1,94 @@
+extern crate self as rayon_adaptive;
+use derive_divisible::{Divisible, DivisibleIntoBlocks};
+use std::marker::PhantomData;
+
+struct IndexedPower;
+
+trait Divisible: Sized {
+    type Power;
+    fn base_length(&self) -> usize;
+    fn divide(self) -> (Self, Self);
+}
+
+trait DivisibleIntoBlocks: Divisible {
+    fn divide_at(self, index: usize) -> (Self, Self);
+}
+
+impl Divisible for Vec<u8> {
+    type Power = IndexedPower;
+    fn base_length(&self) -> usize {
+        self.len()
+    }
+    fn divide(self) -> (Self, Self) {
+        let mid = self.len() / 2;
+        self.divide_at(mid)
+    }
+}
+
+impl DivisibleIntoBlocks for Vec<u8> {
+    fn divide_at(mut self, index: usize) -> (Self, Self) {
+        let right = self.split_off(index);
+        (self, right)
+    }
+}
+
+#[derive(Divisible, DivisibleIntoBlocks, Debug, PartialEq)]
+#[power(IndexedPower)]
+struct Pair(Vec<u8>, #[divide_by(clone)] usize);
+
+#[derive(Divisible, DivisibleIntoBlocks, Debug, PartialEq)]
+#[power(IndexedPower)]
+struct Mixed(#[divide_by(clone)] usize, Vec<u8>, #[divide_by(default)] u32, Vec<u8>);
+
+// the trailing marker needs no attribute
+#[derive(Divisible, DivisibleIntoBlocks, Debug, PartialEq)]
+#[power(IndexedPower)]
+struct Tagged<P>(Vec<u8>, PhantomData<P>);
+
+// many fields, each one split into its own variable
+#[derive(Divisible, DivisibleIntoBlocks)]
+#[power(IndexedPower)]
+#[divide_by(all = "copy")]
+struct Wide(
+    u8,
+    u8,
+    u8,
+    u8,
+    u8,
+    u8,
+    u8,
+    u8,
+    u8,
+    u8,
+    u8,
+    u8,
+    u8,
+    u8,
+    #[divide_by(divisible)] Vec<u8>,
+);
+
+fn main() {
+    assert_eq!(
+        Pair(vec![1, 2, 3, 4, 5], 7).divide_at(2),
+        (Pair(vec![1, 2], 7), Pair(vec![3, 4, 5], 7))
+    );
+    assert_eq!(
+        Pair(vec![1, 2, 3], 7).divide(),
+        (Pair(vec![1], 7), Pair(vec![2, 3], 7))
+    );
+    assert_eq!(
+        Mixed(7, vec![1, 2, 3], 9, vec![4, 5, 6, 7]).divide_at(1),
+        (
+            Mixed(7, vec![1], 9, vec![4]),
+            Mixed(7, vec![2, 3], 0, vec![5, 6, 7])
+        )
+    );
+    assert_eq!(
+        Tagged::<String>(vec![1, 2, 3], PhantomData).divide(),
+        (Tagged(vec![1], PhantomData), Tagged(vec![2, 3], PhantomData))
+    );
+    let wide = Wide(0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, vec![1, 2, 3]);
+    let (left, right) = wide.divide_at(1);
+    assert_eq!((left.13, left.14), (13, vec![1]));
+    assert_eq!((right.0, right.14), (0, vec![2, 3]));
+}
@@ -0,0 +1,1156 @@
+// the mock traits below stand for the ones of rayon-adaptive
+extern crate self as rayon_adaptive;
+use derive_divisible::{Divisible, DivisibleIntoBlocks};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::marker::PhantomData;
+use std::ops::Range;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+
+struct IndexedPower();
+
+trait Divisible: Sized {
+    type Power;
+    fn base_length(&self) -> usize;
+    fn divide(self) -> (Self, Self);
+}
+
+impl<T> Divisible for &[T] {
+    type Power = IndexedPower;
+    fn base_length(&self) -> usize {
+        self.len()
+    }
+    fn divide(self) -> (Self, Self) {
+        let mid = self.len() / 2;
+        self.split_at(mid)
+    }
+}
+
+trait DivisibleIntoBlocks: Divisible {
+    fn divide_at(self, index: usize) -> (Self, Self);
+}
+
+impl<T> DivisibleIntoBlocks for &[T] {
+    fn divide_at(self, index: usize) -> (Self, Self) {
+        self.split_at(index)
+    }
+}
+
+mod budget {
+    pub fn split(budget: u32) -> (u32, u32) {
+        (budget / 2, budget - budget / 2)
+    }
+    pub fn split_at(budget: u32, index: usize) -> (u32, u32) {
+        let left = budget.min(index as u32);
+        (left, budget - left)
+    }
+}
+
+#[derive(Divisible, DivisibleIntoBlocks, Debug, PartialEq)]
+#[power(IndexedPower)]
+struct Named<'a> {
+    #[divide_by(fn = "budget::split", divide_at_fn = "budget::split_at")]
+    budget: u32,
+    data: &'a [u32],
+}
+
+#[derive(Divisible, DivisibleIntoBlocks, Debug, PartialEq)]
+#[power(IndexedPower)]
+struct Unnamed<'a>(
+    #[divide_by(divide_at_fn = "budget::split_at", with = "budget::split")] u32,
+    &'a [u32],
+);
+
+#[derive(Divisible, DivisibleIntoBlocks, Debug, PartialEq)]
+#[power(IndexedPower)]
+struct Quoted<'a> {
+    #[divide_by("clone")]
+    quoted: String,
+    #[divide_by(clone)]
+    unquoted: String,
+    #[divide_by("default")]
+    count: usize,
+    data: &'a [u32],
+}
+
+#[derive(Divisible, DivisibleIntoBlocks, Debug, PartialEq)]
+#[power(IndexedPower)]
+struct Collected<'a, T> {
+    #[divide_by(empty_vec)]
+    found: Vec<T>,
+    data: &'a [u32],
+}
+
+#[derive(Divisible, Debug, PartialEq)]
+#[power(IndexedPower)]
+struct With<'a> {
+    #[divide_by(with = "budget::split")]
+    budget: u32,
+    data: &'a [u32],
+}
+
+// not Clone: a new handle is opened on the same target instead
+#[derive(Debug, PartialEq)]
+struct Output {
+    target: &'static str,
+}
+
+fn reopen(output: &Output) -> Output {
+    Output {
+        target: output.target,
+    }
+}
+
+#[derive(Divisible, DivisibleIntoBlocks, Debug, PartialEq)]
+#[power(IndexedPower)]
+struct Logged<'a> {
+    data: &'a [u32],
+    #[divide_by(dup_with = "reopen")]
+    output: Output,
+}
+
+// (value, count) runs, cut at a position of the decoded sequence
+mod runs {
+    pub type Runs = Vec<(u32, usize)>;
+
+    pub fn split_at(runs: Runs, index: usize) -> (Runs, Runs) {
+        let (mut left, mut right) = (Vec::new(), Vec::new());
+        let mut remaining = index;
+        for (value, count) in runs {
+            let taken = count.min(remaining);
+            remaining -= taken;
+            if taken > 0 {
+                left.push((value, taken));
+            }
+            if taken < count {
+                right.push((value, count - taken));
+            }
+        }
+        (left, right)
+    }
+}
+
+#[derive(Divisible, DivisibleIntoBlocks, Debug, PartialEq)]
+#[power(IndexedPower)]
+struct Encoded<'a> {
+    data: &'a [u32],
+    #[divide_by(with_index = "runs::split_at")]
+    runs: runs::Runs,
+}
+
+// splitting is not the Divisible trait here
+#[derive(Debug, PartialEq)]
+struct Arena(Vec<u32>);
+
+impl Arena {
+    fn split(self) -> (Self, Self) {
+        let middle = self.0.len() / 2;
+        self.split_at(middle)
+    }
+
+    fn split_at(mut self, index: usize) -> (Self, Self) {
+        let right = self.0.split_off(index);
+        (self, Arena(right))
+    }
+}
+
+#[derive(Divisible, Debug, PartialEq)]
+#[power(IndexedPower)]
+struct Allocated<'a> {
+    #[divide_by(with = "Arena::split")]
+    arena: Arena,
+    data: &'a [u32],
+}
+
+#[derive(Divisible, DivisibleIntoBlocks, Debug, PartialEq)]
+#[power(IndexedPower)]
+struct Arenas<'a> {
+    #[divide_by(method = "split", method_at = "split_at")]
+    arena: Arena,
+    data: &'a [u32],
+}
+
+#[derive(Divisible, DivisibleIntoBlocks, Debug, PartialEq)]
+#[power(IndexedPower)]
+struct Owned<T> {
+    #[divide_by(split_off)]
+    items: Vec<T>,
+    #[divide_by(split_off)]
+    queue: VecDeque<u8>,
+    #[divide_by(deque_split)]
+    drained: VecDeque<u8>,
+}
+
+#[derive(Divisible, DivisibleIntoBlocks, Debug, PartialEq)]
+#[power(IndexedPower)]
+struct Sorted<K, V> {
+    #[divide_by(btree_split)]
+    entries: BTreeMap<K, V>,
+}
+
+const LIMIT: u32 = 10;
+
+#[derive(Divisible, DivisibleIntoBlocks, Debug, PartialEq)]
+#[power(IndexedPower)]
+struct Reset<'a> {
+    data: &'a [u32],
+    #[divide_by(default = "LIMIT")]
+    limit: u32,
+    #[divide_by(default = "Vec::with_capacity(LIMIT as usize)")]
+    buffer: Vec<u32>,
+}
+
+#[derive(Divisible, DivisibleIntoBlocks, Debug, PartialEq)]
+#[power(IndexedPower)]
+struct Accumulated<'a> {
+    data: &'a [u32],
+    #[divide_by(default_left)]
+    output: Vec<u32>,
+    #[divide_by(default_left = "LIMIT")]
+    remaining: u32,
+}
+
+#[derive(Divisible, DivisibleIntoBlocks, Debug, PartialEq)]
+#[power(IndexedPower)]
+struct Memoized<'a> {
+    data: &'a [u32],
+    #[divide_by(reset)]
+    cache: HashMap<usize, u32>,
+}
+
+// not Default: only the options get emptied
+#[derive(Debug, PartialEq)]
+struct Handle(u32);
+
+#[derive(Divisible, DivisibleIntoBlocks, Debug, PartialEq)]
+#[power(IndexedPower)]
+struct Taken<'a> {
+    data: &'a [u32],
+    #[divide_by(take)]
+    handle: Option<Handle>,
+    #[divide_by(take = "Some(Handle(0))")]
+    fallback: Option<Handle>,
+}
+
+#[derive(Divisible, DivisibleIntoBlocks, Debug, PartialEq)]
+#[power(IndexedPower)]
+struct Text {
+    #[divide_by(string_chars)]
+    text: String,
+}
+
+#[derive(Divisible, DivisibleIntoBlocks, Debug, PartialEq)]
+#[power(IndexedPower)]
+struct Indices<'a> {
+    #[divide_by(range_split)]
+    range: Range<usize>,
+    data: &'a [u32],
+}
+
+#[derive(Divisible, DivisibleIntoBlocks, Debug, PartialEq)]
+#[power(IndexedPower)]
+struct Interval {
+    #[divide_by(range_split)]
+    range: Range<usize>,
+}
+
+#[derive(Divisible, Debug, PartialEq)]
+#[power(IndexedPower)]
+#[divisible(length = self.data.len().saturating_sub(self.skipped))]
+struct Skipping<'a> {
+    data: &'a [u32],
+    #[divide_by(copy)]
+    skipped: usize,
+}
+
+#[derive(Divisible, DivisibleIntoBlocks, Debug, PartialEq)]
+#[power(IndexedPower)]
+struct Buffered<'a> {
+    items: &'a [u32],
+    #[divide_by(divisible, no_length)]
+    buffer: &'a [u32],
+}
+
+#[derive(Divisible, Debug, PartialEq)]
+#[power(IndexedPower)]
+#[length_field = "items"]
+struct Measured<'a> {
+    items: &'a [u32],
+    scratch: &'a [u32],
+}
+
+#[derive(Divisible, DivisibleIntoBlocks, Debug, PartialEq)]
+#[power(IndexedPower)]
+struct Weighted<'a, T> {
+    #[divide_by(ratio = 3/4)]
+    heavy: &'a [T],
+    light: &'a [T],
+}
+
+// divides by peeling off its first element
+#[derive(Debug, PartialEq)]
+struct Peeling<'a>(&'a [u32]);
+
+impl<'a> Divisible for Peeling<'a> {
+    type Power = IndexedPower;
+    fn base_length(&self) -> usize {
+        self.0.len()
+    }
+    fn divide(self) -> (Self, Self) {
+        let index = self.0.len().min(1);
+        self.divide_at(index)
+    }
+}
+
+impl<'a> DivisibleIntoBlocks for Peeling<'a> {
+    fn divide_at(self, index: usize) -> (Self, Self) {
+        let (left, right) = self.0.split_at(index);
+        (Peeling(left), Peeling(right))
+    }
+}
+
+#[derive(Divisible, Debug, PartialEq)]
+#[power(IndexedPower)]
+struct Halved<'a> {
+    #[divide_by(half)]
+    halved: Peeling<'a>,
+    peeled: Peeling<'a>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Config {
+    threshold: u32,
+}
+
+#[derive(Divisible, DivisibleIntoBlocks, Debug, PartialEq)]
+#[power(IndexedPower)]
+struct Copied<'a>(#[divide_by(copy)] Config, &'a [u32]);
+
+#[derive(Divisible, DivisibleIntoBlocks, Debug, PartialEq)]
+#[power(IndexedPower)]
+struct Identified<'a> {
+    #[divide_by(copy)]
+    id: u64,
+    data: &'a [u32],
+}
+
+#[derive(Divisible, DivisibleIntoBlocks, Debug, PartialEq)]
+#[power(IndexedPower)]
+#[divisible(transparent)]
+struct Wrapped<'a, T> {
+    inner: &'a [u32],
+    marker: PhantomData<T>,
+}
+
+#[derive(Divisible, DivisibleIntoBlocks, Debug, PartialEq)]
+#[power(IndexedPower)]
+#[divisible(transparent)]
+struct Newtype<'a>(&'a [u32]);
+
+// no #[power]: the one of the only field is used
+#[derive(Divisible, Debug, PartialEq)]
+struct Inferred<T>(T);
+
+fn indexed<D: Divisible<Power = IndexedPower>>(divisible: D) -> D {
+    divisible
+}
+
+#[derive(Divisible, DivisibleIntoBlocks, Debug, PartialEq)]
+#[power(IndexedPower)]
+struct Generic<'a, S, L, T>
+where
+    S: Divisible,
+{
+    items: S,
+    others: S,
+    #[divide_by(clone)]
+    label: L,
+    values: &'a [T],
+}
+
+#[derive(Divisible, Debug, PartialEq)]
+#[power(IndexedPower)]
+#[divisible(no_bounds)]
+struct Unbounded<S: Divisible, L: Clone> {
+    items: S,
+    #[divide_by(clone)]
+    label: L,
+}
+
+#[derive(Divisible, Debug, PartialEq)]
+#[power(IndexedPower)]
+#[divisible(bound = "S: Divisible + Send, L: Clone")]
+struct Bounded<S, L> {
+    items: S,
+    #[divide_by(clone)]
+    label: L,
+}
+
+fn divide_in_thread<D: Divisible + Send + 'static>(divisible: D) -> (D, D) {
+    std::thread::spawn(move || divisible.divide())
+        .join()
+        .unwrap()
+}
+
+#[derive(Divisible, DivisibleIntoBlocks)]
+#[power(IndexedPower)]
+struct Typed<'a, T, U> {
+    data: &'a [u32],
+    output: PhantomData<fn() -> T>,
+    input: ::std::marker::PhantomData<*const U>,
+}
+
+// not Clone: only the Arc gets cloned
+#[derive(Debug, PartialEq)]
+struct Table(Vec<u32>);
+
+#[derive(Divisible, DivisibleIntoBlocks, Debug, PartialEq)]
+#[power(IndexedPower)]
+struct Shared<'a, T> {
+    #[divide_by(arc_clone)]
+    table: Arc<T>,
+    data: &'a [u32],
+}
+
+#[derive(Divisible, DivisibleIntoBlocks, Debug, PartialEq)]
+#[power(IndexedPower)]
+struct Local<'a, T> {
+    #[divide_by(rc_clone)]
+    table: Rc<T>,
+    data: &'a [u32],
+}
+
+#[derive(Divisible, DivisibleIntoBlocks, Debug, PartialEq)]
+#[power(IndexedPower)]
+struct Handles<'a> {
+    #[divide_by(share)]
+    table: Arc<Table>,
+    #[divide_by(share)]
+    local: Rc<Table>,
+    data: &'a [u32],
+}
+
+#[derive(Divisible, DivisibleIntoBlocks)]
+#[power(IndexedPower)]
+struct Collecting<'a> {
+    #[divide_by(mutex_clone)]
+    results: Arc<Mutex<Vec<u32>>>,
+    data: &'a [u32],
+}
+
+// no trait in scope here
+mod hidden {
+    #[derive(derive_divisible::Divisible, derive_divisible::DivisibleIntoBlocks)]
+    #[power(crate::IndexedPower)]
+    pub struct Hidden<'a> {
+        pub data: &'a [u32],
+        #[divide_by(default)]
+        pub count: usize,
+    }
+}
+
+// traits re-exported under another path
+mod facade {
+    pub(crate) use super::{Divisible, DivisibleIntoBlocks};
+}
+
+#[derive(Divisible, DivisibleIntoBlocks, Debug, PartialEq)]
+#[power(IndexedPower)]
+#[divisible(crate = "facade")]
+struct Reexported<'a>(&'a [u32]);
+
+#[derive(Divisible, DivisibleIntoBlocks, Debug, PartialEq)]
+#[power(IndexedPower)]
+#[divide_by(all = "clone")]
+struct Settings<'a> {
+    name: String,
+    level: u32,
+    #[divide_by(divisible)]
+    items: &'a [u32],
+}
+
+#[derive(Divisible, Debug, PartialEq)]
+#[power(IndexedPower)]
+#[divide_by(all = "default")]
+struct Counters<'a>(usize, u64, #[divide_by(divisible)] &'a [u32]);
+
+#[derive(DivisibleIntoBlocks, Debug, PartialEq)]
+#[power(IndexedPower)]
+#[divisible(with_length, generate_try_divide, generate_divide_n)]
+#[min_block_size = 64]
+struct Blocks<'a> {
+    #[divide_by(clone)]
+    name: String,
+    items: &'a [u32],
+}
+
+#[test]
+fn fn_and_divide_at_fn() {
+    let v = vec![1, 2, 3, 4];
+    let named = Named {
+        budget: 5,
+        data: &v,
+    };
+    assert_eq!(named.base_length(), 4);
+    assert_eq!(
+        named.divide(),
+        (
+            Named {
+                budget: 2,
+                data: &v[..2]
+            },
+            Named {
+                budget: 3,
+                data: &v[2..]
+            }
+        )
+    );
+    assert_eq!(
+        Named {
+            budget: 5,
+            data: &v
+        }
+        .divide_at(1),
+        (
+            Named {
+                budget: 1,
+                data: &v[..1]
+            },
+            Named {
+                budget: 4,
+                data: &v[1..]
+            }
+        )
+    );
+}
+
+#[test]
+fn tuple_fields() {
+    let v = vec![1, 2, 3, 4];
+    assert_eq!(
+        Unnamed(5, &v).divide(),
+        (Unnamed(2, &v[..2]), Unnamed(3, &v[2..]))
+    );
+    assert_eq!(
+        Unnamed(5, &v).divide_at(3),
+        (Unnamed(3, &v[..3]), Unnamed(2, &v[3..]))
+    );
+}
+
+#[test]
+fn with_function() {
+    let v = vec![1, 2, 3, 4];
+    assert_eq!(
+        With {
+            budget: 3,
+            data: &v
+        }
+        .divide(),
+        (
+            With {
+                budget: 1,
+                data: &v[..2]
+            },
+            With {
+                budget: 2,
+                data: &v[2..]
+            }
+        )
+    );
+}
+
+#[test]
+fn with_associated_function() {
+    let v = vec![1, 2, 3, 4];
+    let allocated = Allocated {
+        arena: Arena(vec![1, 2, 3]),
+        data: &v,
+    };
+    assert_eq!(allocated.base_length(), 4);
+    let (left, right) = allocated.divide();
+    assert_eq!(
+        (left.arena, right.arena),
+        (Arena(vec![1]), Arena(vec![2, 3]))
+    );
+}
+
+#[test]
+fn dup_with() {
+    let v = vec![1, 2, 3, 4];
+    let logged = Logged {
+        data: &v,
+        output: Output { target: "log" },
+    };
+    assert_eq!(logged.base_length(), 4);
+    let (left, right) = logged.divide_at(1);
+    assert_eq!((left.output.target, right.output.target), ("log", "log"));
+    assert_eq!((left.data, right.data), (&v[..1], &v[1..]));
+}
+
+#[test]
+fn with_index() {
+    let v = vec![1, 2, 3, 4];
+    let encoded = Encoded {
+        data: &v,
+        runs: vec![(7, 3), (8, 1)],
+    };
+    let (left, right) = encoded.divide();
+    assert_eq!(
+        (left.runs, &right.runs),
+        (vec![(7, 2)], &vec![(7, 1), (8, 1)])
+    );
+    let (left, right) = right.divide_at(1);
+    assert_eq!((left.runs, right.runs), (vec![(7, 1)], vec![(8, 1)]));
+    let encoded = Encoded {
+        data: &v,
+        runs: vec![(7, 3), (8, 1)],
+    };
+    let (left, right) = encoded.divide_at(3);
+    assert_eq!((left.runs, right.runs), (vec![(7, 3)], vec![(8, 1)]));
+}
+
+#[test]
+fn method_and_method_at() {
+    let v = vec![1, 2, 3, 4];
+    let arenas = Arenas {
+        arena: Arena(vec![1, 2, 3]),
+        data: &v,
+    };
+    let (left, right) = arenas.divide();
+    assert_eq!(
+        (left.arena, right.arena),
+        (Arena(vec![1]), Arena(vec![2, 3]))
+    );
+    let arenas = Arenas {
+        arena: Arena(vec![1, 2, 3]),
+        data: &v,
+    };
+    let (left, right) = arenas.divide_at(2);
+    assert_eq!(
+        (left.arena, right.arena),
+        (Arena(vec![1, 2]), Arena(vec![3]))
+    );
+}
+
+#[test]
+fn ratio() {
+    let v = vec![1, 2, 3, 4];
+    let weighted = Weighted {
+        heavy: &v,
+        light: &v,
+    };
+    let (left, right) = weighted.divide();
+    assert_eq!((left.heavy, right.heavy), (&v[..3], &v[3..]));
+    assert_eq!((left.light, right.light), (&v[..2], &v[2..]));
+    let (left, right) = right.divide();
+    assert_eq!((left.heavy, right.heavy), (&v[3..3], &v[3..]));
+    let weighted = Weighted {
+        heavy: &v,
+        light: &v,
+    };
+    let (left, right) = weighted.divide_at(1);
+    assert_eq!((left.heavy, right.heavy), (&v[..1], &v[1..]));
+    assert_eq!((left.light, right.light), (&v[..1], &v[1..]));
+}
+
+#[test]
+fn half() {
+    let v = vec![1, 2, 3, 4];
+    let halved = Halved {
+        halved: Peeling(&v),
+        peeled: Peeling(&v),
+    };
+    let (left, right) = halved.divide();
+    assert_eq!(
+        (left.halved, right.halved),
+        (Peeling(&v[..2]), Peeling(&v[2..]))
+    );
+    assert_eq!(
+        (left.peeled, right.peeled),
+        (Peeling(&v[..1]), Peeling(&v[1..]))
+    );
+}
+
+#[test]
+fn quoted_strategies() {
+    let v = vec![1, 2, 3, 4];
+    let quoted = Quoted {
+        quoted: "a".to_owned(),
+        unquoted: "b".to_owned(),
+        count: 3,
+        data: &v,
+    };
+    assert_eq!(
+        quoted.divide_at(1),
+        (
+            Quoted {
+                quoted: "a".to_owned(),
+                unquoted: "b".to_owned(),
+                count: 3,
+                data: &v[..1],
+            },
+            Quoted {
+                quoted: "a".to_owned(),
+                unquoted: "b".to_owned(),
+                count: 0,
+                data: &v[1..],
+            }
+        )
+    );
+}
+
+#[test]
+fn empty_vec() {
+    let v = vec![1, 2, 3, 4];
+    let collected = Collected {
+        found: vec![Table(vec![1])],
+        data: &v,
+    };
+    assert_eq!(collected.base_length(), 4);
+    let (left, right) = collected.divide();
+    assert_eq!((left.found, right.found), (vec![Table(vec![1])], vec![]));
+    assert_eq!((left.data, right.data), (&v[..2], &v[2..]));
+}
+
+#[test]
+fn split_off_and_deque_split() {
+    let owned = Owned {
+        items: vec![Table(vec![1]), Table(vec![2]), Table(vec![3])],
+        queue: (0..5).collect(),
+        drained: (0..4).collect(),
+    };
+    assert_eq!(owned.base_length(), 3);
+    let (left, right) = owned.divide();
+    assert_eq!((left.items.len(), right.items.len()), (1, 2));
+    assert_eq!(left.queue, (0..2).collect::<VecDeque<_>>());
+    assert_eq!(right.queue, (2..5).collect::<VecDeque<_>>());
+    assert_eq!(left.drained, (0..2).collect::<VecDeque<_>>());
+    assert_eq!(right.drained, (2..4).collect::<VecDeque<_>>());
+    let (left, right) = right.divide_at(1);
+    assert_eq!(
+        (left.items, right.items),
+        (vec![Table(vec![2])], vec![Table(vec![3])])
+    );
+    assert_eq!((left.queue.len(), right.queue.len()), (1, 2));
+    assert_eq!(
+        (left.drained, right.drained),
+        (VecDeque::from(vec![2]), VecDeque::from(vec![3]))
+    );
+}
+
+#[test]
+fn btree_split() {
+    let sorted = Sorted {
+        entries: (0..5)
+            .map(|i| (i.to_string(), i))
+            .collect::<BTreeMap<_, _>>(),
+    };
+    assert_eq!(sorted.base_length(), 5);
+    let (left, right) = sorted.divide();
+    assert_eq!(left.entries.keys().collect::<Vec<_>>(), vec!["0", "1"]);
+    assert_eq!(right.entries.values().collect::<Vec<_>>(), vec![&2, &3, &4]);
+    let (left, right) = right.divide_at(3);
+    assert_eq!((left.entries.len(), right.entries.len()), (3, 0));
+}
+
+#[test]
+fn default_expression() {
+    let v = vec![1, 2, 3, 4];
+    let reset = Reset {
+        data: &v,
+        limit: 3,
+        buffer: vec![1],
+    };
+    let (left, right) = reset.divide_at(1);
+    assert_eq!((left.limit, &left.buffer), (3, &vec![1]));
+    assert_eq!((right.limit, &right.buffer), (LIMIT, &vec![]));
+    assert!(right.buffer.capacity() >= LIMIT as usize);
+}
+
+#[test]
+fn default_left() {
+    let v = vec![1, 2, 3, 4];
+    let accumulated = Accumulated {
+        data: &v,
+        output: vec![0],
+        remaining: 3,
+    };
+    let (left, right) = accumulated.divide();
+    assert_eq!((left.output, &right.output), (vec![], &vec![0]));
+    assert_eq!((left.remaining, right.remaining), (LIMIT, 3));
+    let (left, right) = right.divide_at(1);
+    assert_eq!((left.data, right.data), (&v[2..3], &v[3..]));
+    assert_eq!((left.output, right.output), (vec![], vec![0]));
+}
+
+#[test]
+fn reset() {
+    let v = vec![1, 2, 3, 4];
+    let memoized = Memoized {
+        data: &v,
+        cache: (0..4).map(|i| (i, v[i])).collect(),
+    };
+    let (left, right) = memoized.divide();
+    assert_eq!((left.data, right.data), (&v[..2], &v[2..]));
+    assert!(left.cache.is_empty() && right.cache.is_empty());
+}
+
+#[test]
+fn take() {
+    let v = vec![1, 2, 3, 4];
+    let taken = Taken {
+        data: &v,
+        handle: Some(Handle(1)),
+        fallback: None,
+    };
+    let (left, right) = taken.divide();
+    assert_eq!((left.handle, left.fallback), (Some(Handle(1)), None));
+    assert_eq!((right.handle, right.fallback), (None, Some(Handle(0))));
+}
+
+#[test]
+fn string_chars() {
+    let text = Text {
+        text: "héllo".to_owned(),
+    };
+    assert_eq!(text.base_length(), 5);
+    let (left, right) = text.divide();
+    assert_eq!((left.text.as_str(), right.text.as_str()), ("hé", "llo"));
+    let (left, right) = left.divide_at(1);
+    assert_eq!((left.text.as_str(), right.text.as_str()), ("h", "é"));
+    let (left, right) = right.divide_at(3);
+    assert_eq!((left.text.as_str(), right.text.as_str()), ("é", ""));
+    let (left, right) = right.divide();
+    assert_eq!((left.base_length(), right.base_length()), (0, 0));
+}
+
+#[test]
+fn range_split() {
+    let v = vec![1, 2, 3, 4];
+    let indices = Indices {
+        range: 10..13,
+        data: &v,
+    };
+    assert_eq!(indices.base_length(), 3);
+    let (left, right) = indices.divide();
+    assert_eq!((&left.range, &right.range), (&(10..11), &(11..13)));
+    assert_eq!((left.data, right.data), (&v[..2], &v[2..]));
+    let (left, right) = right.divide_at(1);
+    assert_eq!((left.range, right.range), (11..12, 12..13));
+    assert_eq!((left.data, right.data), (&v[2..3], &v[3..]));
+}
+
+#[test]
+fn range_split_after_the_end() {
+    let (left, right) = Interval { range: 0..4 }.divide_at(10);
+    assert_eq!((left.range, right.range), (0..4, 4..4));
+    let (left, right) = Interval { range: 2..4 }.divide_at(usize::MAX);
+    assert_eq!((left.range, right.range), (2..4, 4..4));
+}
+
+#[test]
+fn length_expression() {
+    let v = vec![1, 2, 3, 4];
+    let skipping = Skipping {
+        data: &v,
+        skipped: 1,
+    };
+    assert_eq!(skipping.base_length(), 3);
+    assert_eq!(skipping.divide().1.base_length(), 1);
+}
+
+#[test]
+fn no_length() {
+    let v = vec![1, 2, 3, 4];
+    let buffered = Buffered {
+        items: &v[..2],
+        buffer: &v,
+    };
+    assert_eq!(buffered.base_length(), 2);
+    let (left, right) = buffered.divide_at(1);
+    assert_eq!((left.items, right.items), (&v[..1], &v[1..2]));
+    assert_eq!((left.buffer, right.buffer), (&v[..1], &v[1..]));
+}
+
+#[test]
+fn length_field() {
+    let v = vec![1, 2, 3, 4];
+    let measured = Measured {
+        items: &v,
+        scratch: &v[..1],
+    };
+    assert_eq!(measured.base_length(), 4);
+}
+
+#[test]
+fn copy() {
+    let v = vec![1, 2, 3, 4];
+    let config = Config { threshold: 10 };
+    assert_eq!(Copied(config, &v).base_length(), 4);
+    assert_eq!(
+        Copied(config, &v).divide(),
+        (Copied(config, &v[..2]), Copied(config, &v[2..]))
+    );
+    assert_eq!(
+        Copied(config, &v).divide_at(3),
+        (Copied(config, &v[..3]), Copied(config, &v[3..]))
+    );
+}
+
+#[test]
+fn copy_on_both_sides() {
+    let v = [1, 2, 3];
+    let (left, right) = Identified { id: 42, data: &v }.divide();
+    assert_eq!((left.id, right.id), (42, 42));
+    let (left, right) = right.divide_at(1);
+    assert_eq!((left.id, right.id), (42, 42));
+}
+
+#[test]
+fn transparent_with_phantom_data() {
+    let v = vec![1, 2, 3, 4];
+    let wrapped: Wrapped<()> = Wrapped {
+        inner: &v,
+        marker: PhantomData,
+    };
+    assert_eq!(wrapped.base_length(), 4);
+    let (left, right) = wrapped.divide();
+    assert_eq!((left.inner, right.inner), (&v[..2], &v[2..]));
+    let (left, right) = Wrapped::<()> {
+        inner: &v,
+        marker: PhantomData,
+    }
+    .divide_at(1);
+    assert_eq!((left.inner, right.inner), (&v[..1], &v[1..]));
+}
+
+#[test]
+fn transparent_newtype() {
+    let v = vec![1, 2, 3, 4];
+    assert_eq!(Newtype(&v).base_length(), 4);
+    assert_eq!(Newtype(&v).divide(), (Newtype(&v[..2]), Newtype(&v[2..])));
+    assert_eq!(
+        Newtype(&v).divide_at(3),
+        (Newtype(&v[..3]), Newtype(&v[3..]))
+    );
+}
+
+#[test]
+fn power_of_the_only_field() {
+    let v = [1, 2, 3, 4];
+    let inferred = indexed(Inferred(&v[..]));
+    assert_eq!(inferred.divide(), (Inferred(&v[..2]), Inferred(&v[2..])));
+}
+
+#[test]
+fn generic_fields() {
+    let v = [1, 2, 3, 4];
+    let w = [1.5, 2.5];
+    let generic = Generic {
+        items: &v[..],
+        others: &v[..],
+        label: "generic".to_owned(),
+        values: &w[..],
+    };
+    assert_eq!(generic.base_length(), 2);
+    let (left, right) = generic.divide_at(1);
+    assert_eq!((left.items, left.values), (&v[..1], &w[..1]));
+    assert_eq!((right.items, right.values), (&v[1..], &w[1..]));
+    assert_eq!(left.label, right.label);
+}
+
+#[test]
+fn arc_clone() {
+    let v = vec![1, 2, 3, 4];
+    let table = Arc::new(Table(vec![7, 8]));
+    let shared = Shared {
+        table: table.clone(),
+        data: &v,
+    };
+    assert_eq!(shared.base_length(), 4);
+    let (left, right) = shared.divide_at(1);
+    assert!(Arc::ptr_eq(&left.table, &table) && Arc::ptr_eq(&right.table, &table));
+    assert_eq!((left.data, right.data), (&v[..1], &v[1..]));
+}
+
+#[test]
+fn rc_clone() {
+    let v = vec![1, 2, 3, 4];
+    let table = Rc::new(Table(vec![7, 8]));
+    let local = Local {
+        table: table.clone(),
+        data: &v,
+    };
+    let (left, right) = local.divide();
+    assert!(Rc::ptr_eq(&left.table, &table) && Rc::ptr_eq(&right.table, &table));
+    assert_eq!((left.data, right.data), (&v[..2], &v[2..]));
+}
+
+#[test]
+fn share() {
+    let v = vec![1, 2, 3, 4];
+    let table = Rc::new(Table(vec![7, 8]));
+    let handles = Handles {
+        table: Arc::new(Table(vec![1])),
+        local: table.clone(),
+        data: &v,
+    };
+    let (left, right) = handles.divide_at(3);
+    assert!(Arc::ptr_eq(&left.table, &right.table) && Rc::ptr_eq(&left.local, &table));
+    assert!(Rc::ptr_eq(&right.local, &table));
+    assert_eq!((left.data, right.data), (&v[..3], &v[3..]));
+}
+
+#[test]
+fn mutex_clone() {
+    let v = vec![1, 2, 3, 4];
+    let collecting = Collecting {
+        results: Arc::new(Mutex::new(Vec::new())),
+        data: &v,
+    };
+    let (left, right) = collecting.divide();
+    left.results.lock().unwrap().extend(left.data);
+    right.results.lock().unwrap().extend(right.data);
+    assert!(Arc::ptr_eq(&left.results, &right.results));
+    assert_eq!(*left.results.lock().unwrap(), v);
+}
+
+#[test]
+fn no_bounds() {
+    let v = [1, 2, 3, 4];
+    let unbounded = Unbounded {
+        items: &v[..],
+        label: 3,
+    };
+    assert_eq!(
+        unbounded.divide(),
+        (
+            Unbounded {
+                items: &v[..2],
+                label: 3
+            },
+            Unbounded {
+                items: &v[2..],
+                label: 3
+            }
+        )
+    );
+}
+
+#[test]
+fn custom_bounds() {
+    static ITEMS: [u32; 3] = [1, 2, 3];
+    assert_eq!(
+        divide_in_thread(Bounded {
+            items: &ITEMS[..],
+            label: "sent",
+        }),
+        (
+            Bounded {
+                items: &ITEMS[..1],
+                label: "sent"
+            },
+            Bounded {
+                items: &ITEMS[1..],
+                label: "sent"
+            }
+        )
+    );
+}
+
+#[test]
+fn phantom_data() {
+    let v = vec![1, 2, 3, 4];
+    let typed: Typed<Table, Table> = Typed {
+        data: &v,
+        output: PhantomData,
+        input: PhantomData,
+    };
+    assert_eq!(typed.base_length(), 4);
+    let (left, right) = typed.divide_at(3);
+    assert_eq!((left.data, right.data), (&v[..3], &v[3..]));
+}
+
+#[test]
+fn traits_not_in_scope() {
+    let v = vec![1, 2, 3, 4];
+    let hidden = hidden::Hidden { data: &v, count: 2 };
+    assert_eq!(hidden.base_length(), 4);
+    let (left, right) = hidden.divide_at(1);
+    assert_eq!((left.data, left.count), (&v[..1], 2));
+    assert_eq!((right.data, right.count), (&v[1..], 0));
+}
+
+#[test]
+fn crate_path() {
+    let v = vec![1, 2, 3, 4];
+    assert_eq!(
+        Reexported(&v).divide_at(2),
+        (Reexported(&v[..2]), Reexported(&v[2..]))
+    );
+}
+
+#[test]
+fn all_clone() {
+    let v = vec![1, 2, 3, 4];
+    let settings = Settings {
+        name: "fast".to_owned(),
+        level: 3,
+        items: &v,
+    };
+    assert_eq!(settings.base_length(), 4);
+    assert_eq!(
+        settings.divide_at(1),
+        (
+            Settings {
+                name: "fast".to_owned(),
+                level: 3,
+                items: &v[..1],
+            },
+            Settings {
+                name: "fast".to_owned(),
+                level: 3,
+                items: &v[1..],
+            }
+        )
+    );
+}
+
+#[test]
+fn all_default() {
+    let v = vec![1, 2, 3, 4];
+    assert_eq!(
+        Counters(2, 5, &v).divide(),
+        (Counters(2, 5, &v[..2]), Counters(0, 0, &v[2..]))
+    );
+}
+
+#[test]
+fn generated_block_methods() {
+    let v = vec![1, 2, 3, 4];
+    let blocks = Blocks {
+        name: "blocks".to_owned(),
+        items: &v,
+    };
+    assert_eq!(blocks.base_length(), 4);
+    let (left, right) = blocks.divide();
+    assert_eq!((left.items, right.items), (&v[..2], &v[2..]));
+    let (left, right) = right.divide_at(1);
+    assert_eq!((left.items, right.items), (&v[2..3], &v[3..]));
+    assert_eq!(Blocks::MIN_BLOCK_SIZE, 64);
+    assert_eq!(
+        right.try_divide().map(|(_, right)| right.items),
+        Some(&v[3..])
+    );
+    let empty = Blocks {
+        name: "empty".to_owned(),
+        items: &[],
+    };
+    assert_eq!(empty.try_divide(), None);
+    let blocks = Blocks {
+        name: "blocks".to_owned(),
+        items: &v,
+    };
+    let parts: Vec<&[u32]> = blocks.divide_n(3).into_iter().map(|b| b.items).collect();
+    assert_eq!(parts, vec![&v[..2], &v[2..3], &v[3..]]);
+}
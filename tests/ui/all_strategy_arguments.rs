@@ -0,0 +1,38 @@
+use derive_divisible::Divisible;
+
+struct IndexedPower;
+
+trait Divisible: Sized {
+    type Power;
+    fn base_length(&self) -> usize;
+    fn divide(self) -> (Self, Self);
+}
+
+#[derive(Divisible)]
+#[power(IndexedPower)]
+#[divide_by(all = "clone", bogus)]
+struct Extra<'a> {
+    name: String,
+    #[divide_by(divisible)]
+    items: &'a [u32],
+}
+
+#[derive(Divisible)]
+#[power(IndexedPower)]
+#[divide_by(all = "clone", some = "default")]
+struct Unknown<'a> {
+    name: String,
+    #[divide_by(divisible)]
+    items: &'a [u32],
+}
+
+#[derive(Divisible)]
+#[power(IndexedPower)]
+#[divide_by(all = clone)]
+struct Unquoted<'a> {
+    name: String,
+    #[divide_by(divisible)]
+    items: &'a [u32],
+}
+
+fn main() {}
@@ -0,0 +1,20 @@
+use derive_divisible::Divisible;
+
+struct IndexedPower;
+
+trait Divisible: Sized {
+    type Power;
+    fn base_length(&self) -> usize;
+    fn divide(self) -> (Self, Self);
+}
+
+#[derive(Divisible)]
+#[power(IndexedPower)]
+struct Data<'a> {
+    #[divide_by(clone, default)]
+    name: String,
+    #[divide_by(fn = "split", ratio = 1/3)]
+    items: &'a [u32],
+}
+
+fn main() {}
@@ -0,0 +1,32 @@
+extern crate self as rayon_adaptive;
+use derive_divisible::Divisible;
+
+struct IndexedPower;
+
+trait Divisible: Sized {
+    type Power;
+    fn base_length(&self) -> usize;
+    fn divide(self) -> (Self, Self);
+}
+
+impl<T> Divisible for &[T] {
+    type Power = IndexedPower;
+    fn base_length(&self) -> usize {
+        self.len()
+    }
+    fn divide(self) -> (Self, Self) {
+        self.split_at(self.len() / 2)
+    }
+}
+
+struct Registry(Vec<u32>);
+
+#[derive(Divisible)]
+#[power(IndexedPower)]
+struct Data<'a> {
+    #[divide_by(clone)]
+    registry: Registry,
+    items: &'a [u32],
+}
+
+fn main() {}
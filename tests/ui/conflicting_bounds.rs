@@ -0,0 +1,18 @@
+use derive_divisible::Divisible;
+
+struct IndexedPower;
+
+trait Divisible: Sized {
+    type Power;
+    fn base_length(&self) -> usize;
+    fn divide(self) -> (Self, Self);
+}
+
+#[derive(Divisible)]
+#[power(IndexedPower)]
+#[divisible(no_bounds, bound = "S: Divisible")]
+struct Data<S> {
+    items: S,
+}
+
+fn main() {}
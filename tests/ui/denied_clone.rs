@@ -0,0 +1,31 @@
+extern crate self as rayon_adaptive;
+use derive_divisible::Divisible;
+
+struct IndexedPower;
+
+trait Divisible: Sized {
+    type Power;
+    fn base_length(&self) -> usize;
+    fn divide(self) -> (Self, Self);
+}
+
+#[derive(Divisible)]
+#[power(IndexedPower)]
+#[divisible(deny_clone)]
+struct Data<'a> {
+    #[divide_by(clone)]
+    name: String,
+    items: &'a [u32],
+}
+
+#[derive(Divisible)]
+#[power(IndexedPower)]
+#[divisible(deny_clone)]
+#[divide_by(all = "clone")]
+struct Labels<'a> {
+    label: String,
+    #[divide_by(divisible)]
+    items: &'a [u32],
+}
+
+fn main() {}
@@ -0,0 +1,21 @@
+use derive_divisible::Divisible;
+
+struct IndexedPower;
+
+trait Divisible: Sized {
+    type Power;
+    fn base_length(&self) -> usize;
+    fn divide(self) -> (Self, Self);
+}
+
+#[derive(Divisible)]
+#[power(IndexedPower)]
+#[divide_by(all = "clone")]
+#[divide_by(all = "default")]
+struct Data<'a> {
+    name: String,
+    #[divide_by(divisible)]
+    items: &'a [u32],
+}
+
+fn main() {}
@@ -0,0 +1,19 @@
+use derive_divisible::Divisible;
+
+struct BasicPower;
+struct IndexedPower;
+
+trait Divisible: Sized {
+    type Power;
+    fn base_length(&self) -> usize;
+    fn divide(self) -> (Self, Self);
+}
+
+#[derive(Divisible)]
+#[power(BasicPower)]
+#[power(IndexedPower)]
+struct Data<'a> {
+    items: &'a [u32],
+}
+
+fn main() {}
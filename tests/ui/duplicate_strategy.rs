@@ -0,0 +1,20 @@
+use derive_divisible::Divisible;
+
+struct IndexedPower;
+
+trait Divisible: Sized {
+    type Power;
+    fn base_length(&self) -> usize;
+    fn divide(self) -> (Self, Self);
+}
+
+#[derive(Divisible)]
+#[power(IndexedPower)]
+struct Data<'a> {
+    #[divide_by(clone)]
+    #[divide_by(default)]
+    name: String,
+    items: &'a [u32],
+}
+
+fn main() {}
@@ -0,0 +1,18 @@
+use derive_divisible::Divisible;
+
+struct IndexedPower;
+
+trait Divisible: Sized {
+    type Power;
+    fn base_length(&self) -> usize;
+    fn divide(self) -> (Self, Self);
+}
+
+#[derive(Divisible)]
+#[power(IndexedPower)]
+#[divisible(length = )]
+struct Data<'a> {
+    items: &'a [u32],
+}
+
+fn main() {}
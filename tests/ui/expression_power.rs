@@ -0,0 +1,17 @@
+use derive_divisible::Divisible;
+
+struct IndexedPower;
+
+trait Divisible: Sized {
+    type Power;
+    fn base_length(&self) -> usize;
+    fn divide(self) -> (Self, Self);
+}
+
+#[derive(Divisible)]
+#[power(3 + 4)]
+struct Data<'a> {
+    items: &'a [u32],
+}
+
+fn main() {}
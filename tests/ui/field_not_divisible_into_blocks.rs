@@ -0,0 +1,52 @@
+extern crate self as rayon_adaptive;
+use derive_divisible::{Divisible, DivisibleIntoBlocks};
+
+struct IndexedPower;
+
+trait Divisible: Sized {
+    type Power;
+    fn base_length(&self) -> usize;
+    fn divide(self) -> (Self, Self);
+}
+
+trait DivisibleIntoBlocks: Divisible {
+    fn divide_at(self, index: usize) -> (Self, Self);
+}
+
+impl<T> Divisible for &[T] {
+    type Power = IndexedPower;
+    fn base_length(&self) -> usize {
+        self.len()
+    }
+    fn divide(self) -> (Self, Self) {
+        self.split_at(self.len() / 2)
+    }
+}
+
+impl<T> DivisibleIntoBlocks for &[T] {
+    fn divide_at(self, index: usize) -> (Self, Self) {
+        self.split_at(index)
+    }
+}
+
+// can be halved but not cut anywhere
+struct Pair(u32, u32);
+
+impl Divisible for Pair {
+    type Power = IndexedPower;
+    fn base_length(&self) -> usize {
+        2
+    }
+    fn divide(self) -> (Self, Self) {
+        (Pair(self.0, self.0), Pair(self.1, self.1))
+    }
+}
+
+#[derive(Divisible, DivisibleIntoBlocks)]
+#[power(IndexedPower)]
+struct Data<'a> {
+    items: &'a [u32],
+    pair: Pair,
+}
+
+fn main() {}
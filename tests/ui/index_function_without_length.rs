@@ -0,0 +1,24 @@
+use derive_divisible::Divisible;
+
+struct IndexedPower;
+
+trait Divisible: Sized {
+    type Power;
+    fn base_length(&self) -> usize;
+    fn divide(self) -> (Self, Self);
+}
+
+fn split_at(budget: u32, index: usize) -> (u32, u32) {
+    let left = budget.min(index as u32);
+    (left, budget - left)
+}
+
+#[derive(Divisible)]
+#[power(IndexedPower)]
+#[divisible(infinite)]
+struct Budget {
+    #[divide_by(with_index = "split_at")]
+    budget: u32,
+}
+
+fn main() {}
@@ -0,0 +1,23 @@
+use derive_divisible::Divisible;
+
+struct IndexedPower;
+
+trait Divisible: Sized {
+    type Power;
+    fn base_length(&self) -> usize;
+    fn divide(self) -> (Self, Self);
+}
+
+fn split(budget: u32) -> (u32, u32) {
+    (budget / 2, budget - budget / 2)
+}
+
+#[derive(Divisible)]
+#[power(IndexedPower)]
+struct Data<'a> {
+    items: &'a [u32],
+    #[divide_by(with = "split(budget)")]
+    budget: u32,
+}
+
+fn main() {}
@@ -0,0 +1,20 @@
+extern crate self as rayon_adaptive;
+use derive_divisible::Merge;
+
+trait Merge {
+    fn merge(left: Self, right: Self) -> Self;
+}
+
+#[derive(Merge)]
+struct Totals {
+    #[merge_by(sum)]
+    total: u32,
+}
+
+#[derive(Merge)]
+enum Either {
+    Left(u32),
+    Right(u32),
+}
+
+fn main() {}
@@ -0,0 +1,21 @@
+use derive_divisible::Divisible;
+
+struct IndexedPower;
+
+trait Divisible: Sized {
+    type Power;
+    fn base_length(&self) -> usize;
+    fn divide(self) -> (Self, Self);
+}
+
+#[derive(Divisible)]
+#[power(IndexedPower)]
+#[min_block_size = 0]
+struct Empty<'a>(&'a [u32]);
+
+#[derive(Divisible)]
+#[power(IndexedPower)]
+#[min_block_size = "64"]
+struct Quoted<'a>(&'a [u32]);
+
+fn main() {}
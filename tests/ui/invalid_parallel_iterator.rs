@@ -0,0 +1,43 @@
+extern crate self as rayon_adaptive;
+use derive_divisible::ParallelIterator;
+
+trait Divisible: Sized {
+    type Power;
+    fn base_length(&self) -> usize;
+    fn divide(self) -> (Self, Self);
+}
+
+trait ParallelIterator: Divisible {
+    type Item;
+    type SequentialIterator: Iterator<Item = Self::Item>;
+    fn iter(self, size: usize) -> (Self::SequentialIterator, Self);
+}
+
+#[derive(ParallelIterator)]
+struct Zipped<'a> {
+    #[divisible(inner)]
+    left: &'a [u32],
+    #[divisible(inner)]
+    right: &'a [u32],
+}
+
+#[derive(ParallelIterator)]
+struct Unmarked<'a> {
+    items: &'a [u32],
+}
+
+#[derive(ParallelIterator)]
+#[iterator_extraction(iter.map(|x| x + 1))]
+struct Mapped<'a> {
+    #[divisible(inner)]
+    items: &'a [u32],
+}
+
+#[derive(ParallelIterator)]
+#[item(u32, u64)]
+struct Items<'a> {
+    #[divisible(inner)]
+    items: &'a [u32],
+}
+
+fn main() {}
@@ -0,0 +1,16 @@
+use derive_divisible::Divisible;
+
+trait Divisible: Sized {
+    type Power;
+    fn base_length(&self) -> usize;
+    fn divide(self) -> (Self, Self);
+}
+
+#[derive(Divisible)]
+struct Data<'a> {
+    items: &'a [u32],
+    #[divide_by(clone)]
+    name: String,
+}
+
+fn main() {}
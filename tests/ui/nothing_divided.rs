@@ -0,0 +1,27 @@
+use derive_divisible::Divisible;
+
+struct IndexedPower;
+
+trait Divisible: Sized {
+    type Power;
+    fn base_length(&self) -> usize;
+    fn divide(self) -> (Self, Self);
+}
+
+#[derive(Divisible)]
+#[power(IndexedPower)]
+struct Settings {
+    #[divide_by(clone)]
+    name: String,
+    #[divide_by(default)]
+    level: u32,
+}
+
+#[derive(Divisible)]
+#[power(IndexedPower)]
+enum Job {
+    Named(#[divide_by(clone)] String),
+    Idle,
+}
+
+fn main() {}
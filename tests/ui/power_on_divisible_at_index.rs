@@ -0,0 +1,39 @@
+use derive_divisible::DivisibleAtIndex;
+extern crate self as rayon_adaptive;
+
+struct IndexedPower;
+
+trait Divisible: Sized {
+    type Power;
+    fn base_length(&self) -> usize;
+    fn divide(self) -> (Self, Self);
+}
+
+trait DivisibleIntoBlocks: Divisible {
+    fn divide_at(self, index: usize) -> (Self, Self);
+}
+
+trait DivisibleAtIndex: DivisibleIntoBlocks {}
+
+// the power belongs to the (here manual) Divisible implementation
+#[derive(DivisibleAtIndex)]
+#[power(IndexedPower)]
+struct Data;
+
+impl Divisible for Data {
+    type Power = IndexedPower;
+    fn base_length(&self) -> usize {
+        0
+    }
+    fn divide(self) -> (Self, Self) {
+        (Data, Data)
+    }
+}
+
+impl DivisibleIntoBlocks for Data {
+    fn divide_at(self, _index: usize) -> (Self, Self) {
+        (Data, Data)
+    }
+}
+
+fn main() {}
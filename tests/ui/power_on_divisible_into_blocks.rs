@@ -0,0 +1,36 @@
+use derive_divisible::DivisibleIntoBlocks;
+extern crate self as rayon_adaptive;
+
+struct IndexedPower;
+
+trait Divisible: Sized {
+    type Power;
+    fn base_length(&self) -> usize;
+    fn divide(self) -> (Self, Self);
+}
+
+trait DivisibleIntoBlocks: Divisible {
+    fn divide_at(self, index: usize) -> (Self, Self);
+}
+
+// not used without `with_length`, but still checked
+#[derive(DivisibleIntoBlocks)]
+#[power(1 + 2)]
+#[min_block_size = 0]
+#[length_field = "nonexistent"]
+struct Data<'a> {
+    items: &'a [u32],
+}
+
+impl<'a> Divisible for Data<'a> {
+    type Power = IndexedPower;
+    fn base_length(&self) -> usize {
+        self.items.len()
+    }
+    fn divide(self) -> (Self, Self) {
+        let (left, right) = self.items.split_at(self.items.len() / 2);
+        (Data { items: left }, Data { items: right })
+    }
+}
+
+fn main() {}
@@ -0,0 +1,40 @@
+extern crate self as rayon_adaptive;
+use derive_divisible::{Divisible, DivisibleIntoBlocks};
+
+struct IndexedPower;
+
+trait Divisible: Sized {
+    type Power;
+    fn base_length(&self) -> usize;
+    fn divide(self) -> (Self, Self);
+}
+
+trait DivisibleIntoBlocks: Divisible {
+    fn divide_at(self, index: usize) -> (Self, Self);
+}
+
+#[derive(Divisible, DivisibleIntoBlocks)]
+#[power(IndexedPower)]
+struct Tree<'a> {
+    children: Vec<Tree<'a>>,
+    data: &'a [u32],
+}
+
+#[derive(Divisible)]
+#[power(IndexedPower)]
+struct List<'a> {
+    data: &'a [u32],
+    next: Option<Box<Self>>,
+    // not divided so fine
+    #[divide_by(default)]
+    previous: Option<Box<List<'a>>>,
+}
+
+#[derive(Divisible)]
+#[power(IndexedPower)]
+struct Chain<'a> {
+    data: &'a [u32],
+    rest: Option<Box<crate::Chain<'a>>>,
+}
+
+fn main() {}
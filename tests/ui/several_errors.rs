@@ -0,0 +1,16 @@
+use derive_divisible::Divisible;
+
+#[derive(Divisible)]
+#[divisible(crate = "not a path")]
+struct Data<'a> {
+    items: &'a [u32],
+    #[divide_by(colne)]
+    name: String,
+    #[divide_by(defualt)]
+    count: usize,
+    #[divisible(iner)]
+    #[divide_by(clone)]
+    label: String,
+}
+
+fn main() {}
@@ -0,0 +1,22 @@
+use derive_divisible::Divisible;
+
+struct IndexedPower;
+
+trait Divisible: Sized {
+    type Power;
+    fn base_length(&self) -> usize;
+    fn divide(self) -> (Self, Self);
+}
+
+#[derive(Divisible)]
+#[power(IndexedPower)]
+struct Data<'a> {
+    #[divide_by(clone, default)]
+    name: String,
+    items: &'a [u32],
+    // modifiers do not count as strategies but do not allow a second one either
+    #[divide_by(divisible, no_length, copy)]
+    other: &'a [u32],
+}
+
+fn main() {}
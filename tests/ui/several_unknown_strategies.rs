@@ -0,0 +1,23 @@
+use derive_divisible::Divisible;
+
+struct IndexedPower;
+
+trait Divisible: Sized {
+    type Power;
+    fn base_length(&self) -> usize;
+    fn divide(self) -> (Self, Self);
+}
+
+#[derive(Divisible)]
+#[power(IndexedPower)]
+enum Data<'a> {
+    Named {
+        #[divide_by(colne)]
+        name: String,
+        items: &'a [u32],
+    },
+    Counted(#[divide_by(split)] usize, &'a [u32]),
+    Split(#[divide_by(divide_at = "split_at")] Vec<u32>, &'a [u32]),
+}
+
+fn main() {}
@@ -0,0 +1,19 @@
+use derive_divisible::Divisible;
+
+struct IndexedPower;
+
+trait Divisible: Sized {
+    type Power;
+    fn base_length(&self) -> usize;
+    fn divide(self) -> (Self, Self);
+}
+
+#[derive(Divisible)]
+#[power(IndexedPower)]
+#[divide_by(clone)]
+struct Data<'a> {
+    name: String,
+    items: &'a [u32],
+}
+
+fn main() {}
@@ -0,0 +1,30 @@
+extern crate self as rayon_adaptive;
+use derive_divisible::Divisible;
+
+struct IndexedPower;
+
+trait Divisible: Sized {
+    type Power;
+    fn base_length(&self) -> usize;
+    fn divide(self) -> (Self, Self);
+}
+
+impl<T> Divisible for &[T] {
+    type Power = IndexedPower;
+    fn base_length(&self) -> usize {
+        self.len()
+    }
+    fn divide(self) -> (Self, Self) {
+        self.split_at(self.len() / 2)
+    }
+}
+
+#[derive(Divisible)]
+#[power(IndexedPower)]
+#[divisible(transparent)]
+#[length_field = "nope"]
+struct Wrapper<'a> {
+    items: &'a [u32],
+}
+
+fn main() {}
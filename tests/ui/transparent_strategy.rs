@@ -0,0 +1,37 @@
+use derive_divisible::Divisible;
+use std::marker::PhantomData;
+
+struct IndexedPower;
+
+trait Divisible: Sized {
+    type Power;
+    fn base_length(&self) -> usize;
+    fn divide(self) -> (Self, Self);
+}
+
+#[derive(Divisible)]
+#[power(IndexedPower)]
+#[divisible(transparent)]
+struct Cloned<T> {
+    #[divide_by(clone)]
+    items: Vec<T>,
+    marker: PhantomData<T>,
+}
+
+#[derive(Divisible)]
+#[power(IndexedPower)]
+#[divisible(transparent)]
+struct Unmeasured {
+    #[divide_by(divisible, no_length)]
+    items: Vec<u32>,
+}
+
+#[derive(Divisible)]
+#[power(IndexedPower)]
+#[divisible(transparent)]
+#[divide_by(all = "copy")]
+struct Copied {
+    level: u32,
+}
+
+fn main() {}
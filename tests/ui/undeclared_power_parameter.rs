@@ -0,0 +1,17 @@
+use derive_divisible::Divisible;
+
+trait Divisible: Sized {
+    type Power;
+    fn base_length(&self) -> usize;
+    fn divide(self) -> (Self, Self);
+}
+
+#[derive(Divisible)]
+#[power(P)]
+struct Data<'a, Pow> {
+    items: &'a [u32],
+    #[divide_by(clone)]
+    power: Pow,
+}
+
+fn main() {}
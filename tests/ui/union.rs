@@ -0,0 +1,19 @@
+use derive_divisible::{Divisible, DivisibleAtIndex, DivisibleIntoBlocks, FullyDivisible, Merge};
+
+struct IndexedPower;
+
+#[derive(Divisible, DivisibleIntoBlocks, DivisibleAtIndex)]
+#[power(IndexedPower)]
+union Bits {
+    int: u32,
+    float: f32,
+}
+
+#[derive(FullyDivisible, Merge)]
+#[power(IndexedPower)]
+union Word {
+    int: u16,
+    bytes: [u8; 2],
+}
+
+fn main() {}
@@ -0,0 +1,28 @@
+use derive_divisible::Divisible;
+
+struct IndexedPower;
+
+trait Divisible: Sized {
+    type Power;
+    fn base_length(&self) -> usize;
+    fn divide(self) -> (Self, Self);
+}
+
+#[derive(Divisible)]
+#[power(IndexedPower)]
+#[divisible(generate_try_divid)]
+#[divisible(infinite, deny_clone, infinite)]
+struct Settings {
+    #[divide_by(copy)]
+    level: u32,
+}
+
+#[derive(Divisible)]
+#[power(IndexedPower)]
+#[divisible(crate = rayon_adaptive, inner)]
+enum Job {
+    #[divisible(transparent)]
+    Running(#[divisible(empty)] Vec<u32>),
+}
+
+fn main() {}
@@ -0,0 +1,21 @@
+use derive_divisible::DivisibleIntoBlocks;
+
+trait Divisible: Sized {
+    type Power;
+    fn base_length(&self) -> usize;
+    fn divide(self) -> (Self, Self);
+}
+
+trait DivisibleIntoBlocks: Divisible {
+    fn divide_at(self, index: usize) -> (Self, Self);
+}
+
+#[derive(DivisibleIntoBlocks)]
+#[divisible(with_length)]
+struct Data<'a> {
+    items: &'a [u32],
+    #[divide_by(clone)]
+    name: String,
+}
+
+fn main() {}